@@ -31,6 +31,10 @@ cfg_if::cfg_if! {
 }
 
 impl ClockControl for Gd32ClockControl {
+    // The RCU has no per-peripheral ready indication, the default barrier
+    // is all that is needed.
+    type Peripheral = ();
+
     fn init() {
         // GD32 specific clock initialization code
         unsafe {
@@ -308,7 +308,8 @@ unsafe fn enable_fpu() {
     temp |= SCB_CPACR_FULL_ACCESS << (4 * 2);
     temp |= 0x00F00000;
     SCB_CPACR_PTR.write_volatile(temp);
-    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+    blueos_hal::barrier::dsb();
+    blueos_hal::barrier::isb();
 }
 
 pub unsafe fn init_soc() {
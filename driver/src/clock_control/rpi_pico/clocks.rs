@@ -19,10 +19,12 @@
 // Copyright Tock Contributors 2022.
 
 use crate::static_ref::StaticRef;
+use blueos_hal::err::{HalError, Result};
 use tock_registers::{
-    interfaces::{ReadWriteable, Readable},
+    interfaces::{ReadWriteable, Readable, Writeable},
     register_bitfields, register_structs,
     registers::{ReadOnly, ReadWrite},
+    RegisterLongName,
 };
 
 register_structs! {
@@ -277,51 +279,105 @@ pub fn disable_clk_sys_resus() {
         .modify(CLK_SYS_RESUS_CTRL::ENABLE::CLEAR);
 }
 
-pub fn disable_sys_aux() {
+// A glitchless mux switches within a few cycles of the old and the new
+// source. This bounds the wait far above that, even from the 32 kHz LPOSC,
+// while still giving up on a source that never starts.
+const SWITCH_POLLS: u32 = 1_000_000;
+
+/// Waits until the one-hot SELECTED register of a glitchless mux reports
+/// `src`.
+fn wait_selected<R: RegisterLongName>(selected: &ReadOnly<u32, R>, src: u32) -> Result<()> {
+    for _ in 0..SWITCH_POLLS {
+        if selected.get() == 1 << src {
+            return Ok(());
+        }
+    }
+    Err(HalError::Timeout)
+}
+
+pub fn disable_sys_aux() -> Result<()> {
     CLOCKS_BASE.clk_sys_ctrl.modify(CLK_SYS_CTRL::SRC::CLK_REF);
-    while !CLOCKS_BASE.clk_sys_selected.is_set(CLK_SYS_SELECTED::VALUE) {}
+    wait_selected(
+        &CLOCKS_BASE.clk_sys_selected,
+        SystemClockSource::Reference as u32,
+    )
 }
 
-pub fn disable_ref_aux() {
+pub fn disable_ref_aux() -> Result<()> {
     CLOCKS_BASE
         .clk_ref_ctrl
         .modify(CLK_REF_CTRL::SRC::ROSC_CLKSRC_PH);
-    while !CLOCKS_BASE.clk_ref_selected.is_set(CLK_REF_SELECTED::VALUE) {}
+    wait_selected(
+        &CLOCKS_BASE.clk_ref_selected,
+        ReferenceClockSource::Rsoc as u32,
+    )
 }
 
+/// Switches clk_sys to `src_clk`, glitchlessly.
+///
+/// AUXSRC glitches when changed while selected, so clk_sys first moves to
+/// clk_ref if it runs from the auxiliary mux. A divisor larger than the
+/// current one is set before the switch and a smaller one after it, so the
+/// output never runs faster than either setting. Returns `Timeout` if the
+/// mux does not confirm a switch.
 pub fn configure_system_clock(
     src_clk: SystemClockSource,
     aux_clk: SystemAuxiliaryClockSource,
     div_int: u16,
     div_frac: u16,
-) {
+) -> Result<()> {
     let clk_sys = &CLOCKS_BASE.clk_sys_ctrl;
     let clk_sys_div = &CLOCKS_BASE.clk_sys_div;
     let clk_sys_selected = &CLOCKS_BASE.clk_sys_selected;
 
-    clk_sys.modify(CLK_SYS_CTRL::SRC.val(src_clk as u32));
-    clk_sys.modify(CLK_SYS_CTRL::AUXSRC.val(aux_clk as u32));
+    let div = CLK_SYS_DIV::INT.val(div_int as u32) + CLK_SYS_DIV::FRAC.val(div_frac as u32);
+    let raising = (((div_int as u32) << 16) | div_frac as u32) > clk_sys_div.get();
+    if raising {
+        clk_sys_div.write(div);
+    }
 
-    clk_sys_div.modify(CLK_SYS_DIV::INT.val(div_int as u32));
-    clk_sys_div.modify(CLK_SYS_DIV::FRAC.val(div_frac as u32));
+    if clk_sys.read(CLK_SYS_CTRL::SRC) == SystemClockSource::Auxiliary as u32 {
+        clk_sys.modify(CLK_SYS_CTRL::SRC::CLK_REF);
+        wait_selected(clk_sys_selected, SystemClockSource::Reference as u32)?;
+    }
+    clk_sys.modify(CLK_SYS_CTRL::AUXSRC.val(aux_clk as u32));
+    clk_sys.modify(CLK_SYS_CTRL::SRC.val(src_clk as u32));
+    wait_selected(clk_sys_selected, src_clk as u32)?;
 
-    while !clk_sys_selected.is_set(CLK_SYS_SELECTED::VALUE) {}
+    if !raising {
+        clk_sys_div.write(div);
+    }
+    Ok(())
 }
 
+/// Switches clk_ref to `src_clk`, glitchlessly, see
+/// [`configure_system_clock`].
 pub fn configure_reference_clock(
     src_clk: ReferenceClockSource,
     aux_clk: ReferenceAuxiliaryClockSource,
     div: u8,
-) {
+) -> Result<()> {
     let clk_ref = &CLOCKS_BASE.clk_ref_ctrl;
     let clk_ref_div = &CLOCKS_BASE.clk_ref_div;
     let clk_ref_selected = &CLOCKS_BASE.clk_ref_selected;
 
-    clk_ref.modify(CLK_REF_CTRL::SRC.val(src_clk as u32));
+    let raising = div as u32 > clk_ref_div.read(CLK_REF_DIV::INT);
+    if raising {
+        clk_ref_div.write(CLK_REF_DIV::INT.val(div as u32));
+    }
+
+    if clk_ref.read(CLK_REF_CTRL::SRC) == ReferenceClockSource::Auxiliary as u32 {
+        clk_ref.modify(CLK_REF_CTRL::SRC::XOSC_CLKSRC);
+        wait_selected(clk_ref_selected, ReferenceClockSource::Xosc as u32)?;
+    }
     clk_ref.modify(CLK_REF_CTRL::AUXSRC.val(aux_clk as u32));
-    clk_ref_div.modify(CLK_REF_DIV::INT.val(div as u32));
+    clk_ref.modify(CLK_REF_CTRL::SRC.val(src_clk as u32));
+    wait_selected(clk_ref_selected, src_clk as u32)?;
 
-    while !clk_ref_selected.is_set(CLK_REF_SELECTED::VALUE) {}
+    if !raising {
+        clk_ref_div.write(CLK_REF_DIV::INT.val(div as u32));
+    }
+    Ok(())
 }
 
 pub fn configure_peripheral_clock(aux_clk: PeripheralAuxiliaryClockSource) {
@@ -338,3 +394,109 @@ pub fn configure_peripheral_clock(aux_clk: PeripheralAuxiliaryClockSource) {
 
     clk_peri.modify(CLK_PERI_CTRL::ENABLE::SET);
 }
+
+/// One clock generator as read back from its registers.
+#[derive(Clone, Copy, Debug)]
+pub struct ClockNode {
+    /// Selected source, `aux` sources are named after the auxiliary mux.
+    pub source: &'static str,
+    /// Divisor in 16.16 fixed point.
+    pub div: u32,
+    pub enabled: bool,
+    /// Output frequency, `None` if the source frequency is not known, as
+    /// for the ring oscillator and the GPIO inputs.
+    pub frequency: Option<u32>,
+}
+
+/// Input frequencies the clock generators can be derived from.
+pub struct ClockInputs {
+    pub xosc: u32,
+    pub pll_sys: u32,
+    pub pll_usb: u32,
+}
+
+const LPOSC_FREQ: u32 = 32_768;
+
+fn divide(source: Option<u32>, div: u32) -> Option<u32> {
+    source.map(|f| ((f as u64) << 16).checked_div(div as u64).unwrap_or(0) as u32)
+}
+
+// A zero integer divisor stands for the largest one, 2^bits.
+fn div_16_16(int: u32, bits: u32, frac: u32) -> u32 {
+    let int = if int == 0 { 1 << bits } else { int };
+    int.saturating_mul(1 << 16).saturating_add(frac)
+}
+
+pub fn read_reference_clock(inputs: &ClockInputs) -> ClockNode {
+    let ctrl = &CLOCKS_BASE.clk_ref_ctrl;
+    let (source, frequency) = match ctrl.read(CLK_REF_CTRL::SRC) {
+        0 => ("rosc", None),
+        1 => match ctrl.read(CLK_REF_CTRL::AUXSRC) {
+            0 => ("aux pll_usb", Some(inputs.pll_usb)),
+            1 => ("aux gpin0", None),
+            2 => ("aux gpin1", None),
+            _ => ("aux pll_usb_primary_ref_opcg", None),
+        },
+        2 => ("xosc", Some(inputs.xosc)),
+        _ => ("lposc", Some(LPOSC_FREQ)),
+    };
+    let div = div_16_16(CLOCKS_BASE.clk_ref_div.read(CLK_REF_DIV::INT), 8, 0);
+    ClockNode {
+        source,
+        div,
+        enabled: true,
+        frequency: divide(frequency, div),
+    }
+}
+
+pub fn read_system_clock(inputs: &ClockInputs, clk_ref: &ClockNode) -> ClockNode {
+    let ctrl = &CLOCKS_BASE.clk_sys_ctrl;
+    let (source, frequency) = match ctrl.read(CLK_SYS_CTRL::SRC) {
+        0 => ("clk_ref", clk_ref.frequency),
+        _ => match ctrl.read(CLK_SYS_CTRL::AUXSRC) {
+            0 => ("aux pll_sys", Some(inputs.pll_sys)),
+            1 => ("aux pll_usb", Some(inputs.pll_usb)),
+            2 => ("aux rosc", None),
+            3 => ("aux xosc", Some(inputs.xosc)),
+            4 => ("aux gpin0", None),
+            _ => ("aux gpin1", None),
+        },
+    };
+    let div_reg = &CLOCKS_BASE.clk_sys_div;
+    let div = div_16_16(
+        div_reg.read(CLK_SYS_DIV::INT),
+        16,
+        div_reg.read(CLK_SYS_DIV::FRAC),
+    );
+    ClockNode {
+        source,
+        div,
+        enabled: true,
+        frequency: divide(frequency, div),
+    }
+}
+
+pub fn read_peripheral_clock(inputs: &ClockInputs, clk_sys: &ClockNode) -> ClockNode {
+    let ctrl = &CLOCKS_BASE.clk_peri_ctrl;
+    let (source, frequency) = match ctrl.read(CLK_PERI_CTRL::AUXSRC) {
+        0 => ("aux clk_sys", clk_sys.frequency),
+        1 => ("aux pll_sys", Some(inputs.pll_sys)),
+        2 => ("aux pll_usb", Some(inputs.pll_usb)),
+        3 => ("aux rosc", None),
+        4 => ("aux xosc", Some(inputs.xosc)),
+        5 => ("aux gpin0", None),
+        _ => ("aux gpin1", None),
+    };
+    let enabled = ctrl.is_set(CLK_PERI_CTRL::ENABLED);
+    let div = div_16_16(CLOCKS_BASE.clk_peri_div.read(CLK_PERI_DIV::INT), 2, 0);
+    ClockNode {
+        source,
+        div,
+        enabled,
+        frequency: if enabled {
+            divide(frequency, div)
+        } else {
+            Some(0)
+        },
+    }
+}
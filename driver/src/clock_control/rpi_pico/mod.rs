@@ -20,17 +20,28 @@ use crate::clock_control::{
     rpi_pico as rp235x,
     rpi_pico::{
         clocks::{
-            PeripheralAuxiliaryClockSource, ReferenceAuxiliaryClockSource, ReferenceClockSource,
-            SystemAuxiliaryClockSource, SystemClockSource,
+            ClockInputs, PeripheralAuxiliaryClockSource, ReferenceAuxiliaryClockSource,
+            ReferenceClockSource, SystemAuxiliaryClockSource, SystemClockSource,
         },
         pll::PLLConfig,
-        reset::{Peripheral, Resets},
+        reset::Resets,
     },
 };
-use blueos_hal::clock_control::ClockControl;
+use blueos_hal::{
+    clock_control::ClockControl,
+    err::{HalError, Result},
+};
+use core::fmt;
+
+pub use clocks::ClockNode;
+pub use pll::PllState;
+pub use reset::Peripheral;
 
 pub struct RpiPicoClockControl;
 
+/// Frequency of the crystal on the supported boards.
+pub const XOSC_FREQ: u32 = 12_000_000;
+
 pub const PLL_SYS_150MHZ: PLLConfig = PLLConfig {
     fbdiv: 125,
     refdiv: 1,
@@ -45,13 +56,22 @@ pub const PLL_USB_48MHZ: PLLConfig = PLLConfig {
     postdiv2: 5,
 };
 
+// Leaving reset takes a few cycles of the peripheral clock. Like the
+// glitchless switch wait, this bounds it far above that, so that a
+// peripheral still held in reset fails instead of hanging the boot.
+const READY_POLLS: u32 = 1_000_000;
+
 impl ClockControl for RpiPicoClockControl {
+    type Peripheral = Peripheral;
+
     fn init() {
-        let _ = rp235x::xosc::start_xosc(12_000_000);
+        let _ = rp235x::xosc::start_xosc(XOSC_FREQ as usize);
 
+        // Running on with a clock that did not switch would only fail later
+        // and less clearly, e.g. with a wrong baud rate.
         rp235x::clocks::disable_clk_sys_resus();
-        rp235x::clocks::disable_sys_aux();
-        rp235x::clocks::disable_ref_aux();
+        rp235x::clocks::disable_sys_aux().expect("clk_sys did not switch to clk_ref");
+        rp235x::clocks::disable_ref_aux().expect("clk_ref did not switch to rosc");
 
         let reset = Resets::new();
 
@@ -80,23 +100,121 @@ impl ClockControl for RpiPicoClockControl {
         reset.unreset(&[Peripheral::PllSys, Peripheral::PllUsb], true);
 
         let pll_sys_freq =
-            rp235x::pll::configure_pll(rp235x::pll::PLL::Sys, 12_000_000, &PLL_SYS_150MHZ);
+            rp235x::pll::configure_pll(rp235x::pll::PLL::Sys, XOSC_FREQ, &PLL_SYS_150MHZ);
         let pll_usb_freq =
-            rp235x::pll::configure_pll(rp235x::pll::PLL::Usb, 12_000_000, &PLL_USB_48MHZ);
+            rp235x::pll::configure_pll(rp235x::pll::PLL::Usb, XOSC_FREQ, &PLL_USB_48MHZ);
 
         rp235x::clocks::configure_reference_clock(
             ReferenceClockSource::Xosc,
             ReferenceAuxiliaryClockSource::PllUsb,
             1,
-        );
+        )
+        .expect("clk_ref did not switch to xosc");
 
         rp235x::clocks::configure_system_clock(
             SystemClockSource::Auxiliary,
             SystemAuxiliaryClockSource::PllSys,
             1,
             0,
-        );
+        )
+        .expect("clk_sys did not switch to pll_sys");
 
         rp235x::clocks::configure_peripheral_clock(PeripheralAuxiliaryClockSource::PllSys);
+        // The first access to a peripheral must not race its clock enable.
+        blueos_hal::barrier::dsb();
+    }
+
+    // RESET_DONE is only set once the peripheral is out of reset and its
+    // clock is running.
+    fn wait_peripheral_ready(peripheral: Peripheral) -> Result<()> {
+        let resets = Resets::new();
+        for _ in 0..READY_POLLS {
+            if resets.is_done(&peripheral) {
+                blueos_hal::barrier::dsb();
+                return Ok(());
+            }
+        }
+        Err(HalError::Timeout)
+    }
+}
+
+/// The clock tree as read back from the hardware, see
+/// [`RpiPicoClockControl::dump_tree`].
+#[derive(Clone, Copy, Debug)]
+pub struct ClockTree {
+    pub xosc: u32,
+    pub pll_sys: PllState,
+    pub pll_usb: PllState,
+    pub clk_ref: ClockNode,
+    pub clk_sys: ClockNode,
+    pub clk_peri: ClockNode,
+}
+
+impl RpiPicoClockControl {
+    /// Reads back the source and divisor of each clock and computes the
+    /// frequency at every node, assuming an `XOSC_FREQ` crystal. Print it
+    /// through the logger to check the result of `init`.
+    pub fn dump_tree() -> ClockTree {
+        let pll_sys = rp235x::pll::read_pll(rp235x::pll::PLL::Sys);
+        let pll_usb = rp235x::pll::read_pll(rp235x::pll::PLL::Usb);
+        let inputs = ClockInputs {
+            xosc: XOSC_FREQ,
+            pll_sys: pll_sys.frequency(XOSC_FREQ),
+            pll_usb: pll_usb.frequency(XOSC_FREQ),
+        };
+        let clk_ref = rp235x::clocks::read_reference_clock(&inputs);
+        let clk_sys = rp235x::clocks::read_system_clock(&inputs, &clk_ref);
+        let clk_peri = rp235x::clocks::read_peripheral_clock(&inputs, &clk_sys);
+        ClockTree {
+            xosc: XOSC_FREQ,
+            pll_sys,
+            pll_usb,
+            clk_ref,
+            clk_sys,
+            clk_peri,
+        }
+    }
+}
+
+fn write_pll(f: &mut fmt::Formatter<'_>, name: &str, pll: &PllState, xosc: u32) -> fmt::Result {
+    let c = &pll.config;
+    writeln!(
+        f,
+        "{name}: xosc /{} *{} /{}/{} = {} Hz{}{}",
+        c.refdiv,
+        c.fbdiv,
+        c.postdiv1,
+        c.postdiv2,
+        pll.frequency(xosc),
+        if pll.locked { "" } else { ", unlocked" },
+        if pll.bypass { ", bypassed" } else { "" }
+    )
+}
+
+fn write_node(f: &mut fmt::Formatter<'_>, name: &str, node: &ClockNode) -> fmt::Result {
+    write!(
+        f,
+        "{name}: {} /{}.{:04}",
+        node.source,
+        node.div >> 16,
+        ((node.div & 0xFFFF) * 10_000) >> 16
+    )?;
+    match (node.enabled, node.frequency) {
+        (false, _) => write!(f, " disabled"),
+        (true, Some(hz)) => write!(f, " = {hz} Hz"),
+        (true, None) => write!(f, " = unknown"),
+    }
+}
+
+impl fmt::Display for ClockTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "xosc: {} Hz", self.xosc)?;
+        write_pll(f, "pll_sys", &self.pll_sys, self.xosc)?;
+        write_pll(f, "pll_usb", &self.pll_usb, self.xosc)?;
+        write_node(f, "clk_ref", &self.clk_ref)?;
+        writeln!(f)?;
+        write_node(f, "clk_sys", &self.clk_sys)?;
+        writeln!(f)?;
+        write_node(f, "clk_peri", &self.clk_peri)
     }
 }
@@ -97,7 +97,7 @@ pub enum PLL {
     Usb,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct PLLConfig {
     pub fbdiv: u32,
     pub refdiv: u32,
@@ -105,6 +105,51 @@ pub struct PLLConfig {
     pub postdiv2: u32,
 }
 
+/// Settings of a PLL as read back from its registers.
+#[derive(Clone, Copy, Debug)]
+pub struct PllState {
+    pub config: PLLConfig,
+    pub powered: bool,
+    pub locked: bool,
+    pub bypass: bool,
+}
+
+impl PllState {
+    /// Output frequency for the reference `xosc_freq`, 0 while the PLL is
+    /// down or unlocked.
+    pub fn frequency(&self, xosc_freq: u32) -> u32 {
+        let c = &self.config;
+        if self.bypass {
+            xosc_freq
+        } else if !self.powered || !self.locked || c.refdiv == 0 || c.postdiv1 * c.postdiv2 == 0 {
+            0
+        } else {
+            (xosc_freq as u64 / c.refdiv as u64 * c.fbdiv as u64 / (c.postdiv1 * c.postdiv2) as u64)
+                as u32
+        }
+    }
+}
+
+pub fn read_pll(clock: PLL) -> PllState {
+    let pll_base = match clock {
+        PLL::Sys => PLL_SYS_BASE,
+        PLL::Usb => PLL_USB_BASE,
+    };
+    PllState {
+        config: PLLConfig {
+            fbdiv: pll_base.fbdiv_int.read(FBDIV_INT::FBDIV_INT),
+            refdiv: pll_base.cs.read(CS::REFDIV),
+            postdiv1: pll_base.prim.read(PRIM::POSTDIV1),
+            postdiv2: pll_base.prim.read(PRIM::POSTDIV2),
+        },
+        powered: !pll_base.pwr.is_set(PWR::PD)
+            && !pll_base.pwr.is_set(PWR::VCOPD)
+            && !pll_base.pwr.is_set(PWR::POSTDIVPD),
+        locked: pll_base.cs.is_set(CS::LOCK),
+        bypass: pll_base.cs.is_set(CS::BYPASS),
+    }
+}
+
 pub fn configure_pll(clock: PLL, xosc_freq: u32, config: &PLLConfig) -> u32 {
     let ref_freq = xosc_freq / config.refdiv;
 
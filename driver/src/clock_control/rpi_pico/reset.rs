@@ -398,6 +398,12 @@ impl Resets {
         }
     }
 
+    pub fn is_done(&self, peripheral: &Peripheral) -> bool {
+        self.registers
+            .reset_done
+            .matches_all(peripheral.get_reset_done_field_set())
+    }
+
     pub fn reset_all_except(&self, peripherals: &'static [Peripheral]) {
         let mut value = 0xFFFFFF;
         for peripheral in peripherals {
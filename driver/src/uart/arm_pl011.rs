@@ -14,13 +14,14 @@
 
 // SPDX-FileCopyrightText: Copyright 2023-2024 Arm Limited and/or its affiliates <open-source-office@arm.com>
 // SPDX-License-Identifier: MIT OR Apache-2.0
-use crate::uart::{DataBits, Parity, StopBits};
+use crate::uart::{DataBits, FlowCtrl, Parity, RxErrors, StopBits};
 use bitflags::bitflags;
 use blueos_hal::{
     err::{HalError, Result},
+    power::WakeSource,
     uart::Uart,
-    Configuration, Has8bitDataReg, HasFifo, HasInterruptReg, HasLineStatusReg, HasStatusReg,
-    PlatPeri,
+    Configuration, HandlesIrq, Has8bitDataReg, HasFifo, HasInterruptReg, HasLineStatusReg,
+    HasStatusReg, PlatPeri,
 };
 use core::{cell::UnsafeCell, fmt, ptr::NonNull};
 use safe_mmio::{
@@ -179,6 +180,27 @@ bitflags! {
 /// Set all interrupts from bit 0 to 10
 pub const ALL_INTERRUPTS: Interrupts = Interrupts::from_bits_truncate(0x7FF);
 
+const RX_ERRORS: Interrupts = Interrupts::OEI
+    .union(Interrupts::BEI)
+    .union(Interrupts::PEI)
+    .union(Interrupts::FEI);
+
+// Interrupts behind an `InterruptType`. `All` leaves out the modem
+// interrupts, which nothing serves.
+fn interrupt_mask(intr: &super::InterruptType) -> Interrupts {
+    match intr {
+        super::InterruptType::All => Interrupts::RXI
+            .union(Interrupts::TXI)
+            .union(Interrupts::RTI)
+            .union(RX_ERRORS),
+        super::InterruptType::Rx => Interrupts::RXI,
+        super::InterruptType::Tx => Interrupts::TXI,
+        super::InterruptType::RxTimeout => Interrupts::RTI,
+        super::InterruptType::Error => RX_ERRORS,
+        _ => Interrupts::empty(),
+    }
+}
+
 /// PL011 register map
 #[derive(Clone, Eq, FromBytes, Immutable, IntoBytes, KnownLayout, PartialEq)]
 #[repr(C, align(4))]
@@ -235,7 +257,40 @@ pub struct PL011Registers {
     uartpcellid3: ReadPure<u32>,
 }
 
+// A miscounted reserved array shifts every register after it, so check the
+// layout against the documented offsets at build time.
+const _: () = {
+    use core::mem::{offset_of, size_of};
+    assert!(offset_of!(PL011Registers, uartdr) == 0x000);
+    assert!(offset_of!(PL011Registers, uartrsr_ecr) == 0x004);
+    assert!(offset_of!(PL011Registers, uartfr) == 0x018);
+    assert!(offset_of!(PL011Registers, uartilpr) == 0x020);
+    assert!(offset_of!(PL011Registers, uartibrd) == 0x024);
+    assert!(offset_of!(PL011Registers, uartfbrd) == 0x028);
+    assert!(offset_of!(PL011Registers, uartlcr_h) == 0x02C);
+    assert!(offset_of!(PL011Registers, uartcr) == 0x030);
+    assert!(offset_of!(PL011Registers, uartifls) == 0x034);
+    assert!(offset_of!(PL011Registers, uartimsc) == 0x038);
+    assert!(offset_of!(PL011Registers, uartris) == 0x03C);
+    assert!(offset_of!(PL011Registers, uartmis) == 0x040);
+    assert!(offset_of!(PL011Registers, uarticr) == 0x044);
+    assert!(offset_of!(PL011Registers, uartdmacr) == 0x048);
+    assert!(offset_of!(PL011Registers, uartperiphid0) == 0xFE0);
+    assert!(offset_of!(PL011Registers, uartperiphid1) == 0xFE4);
+    assert!(offset_of!(PL011Registers, uartperiphid2) == 0xFE8);
+    assert!(offset_of!(PL011Registers, uartperiphid3) == 0xFEC);
+    assert!(offset_of!(PL011Registers, uartpcellid0) == 0xFF0);
+    assert!(offset_of!(PL011Registers, uartpcellid1) == 0xFF4);
+    assert!(offset_of!(PL011Registers, uartpcellid2) == 0xFF8);
+    assert!(offset_of!(PL011Registers, uartpcellid3) == 0xFFC);
+    assert!(size_of::<PL011Registers>() == 0x1000);
+};
+
 /// RX/TX interrupt FIFO levels
+///
+/// The levels are fractions of the FIFO depth, from 1/8 to 7/8. The names give
+/// the byte count for the 32-entry FIFO of r1p5, earlier revisions only have
+/// 16 entries and trigger at half these counts.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum FifoLevel {
     Bytes4 = 0b000,
@@ -245,6 +300,56 @@ pub enum FifoLevel {
     Bytes28 = 0b100,
 }
 
+/// Bytes at the trigger level selected by a 3-bit UARTIFLS field.
+fn trigger_bytes(field: u32, depth: u8) -> u8 {
+    let eighths = match field & 0b111 {
+        0b000 => 1,
+        0b001 => 2,
+        0b010 => 4,
+        0b011 => 6,
+        _ => 7,
+    };
+    (depth as u32 * eighths / 8) as u8
+}
+
+/// RX trigger level `configure` picks for `baudrate` when none is given.
+///
+/// At low rates a byte takes long enough that interrupting for each one is
+/// cheap, and an interactive console wants every keystroke at once, so the
+/// lowest level is used up to 19200 baud. Up to 230400 baud the FIFO
+/// triggers half full. Above that it triggers at 3/4, which still leaves a
+/// quarter of the FIFO, 4 bytes or about 40us at 921600 baud on a 16-entry
+/// FIFO, for the interrupt to be serviced before it overruns.
+pub const fn rx_trigger_for_baud(baudrate: u32) -> FifoLevel {
+    if baudrate <= 19_200 {
+        FifoLevel::Bytes4
+    } else if baudrate <= 230_400 {
+        FifoLevel::Bytes16
+    } else {
+        FifoLevel::Bytes24
+    }
+}
+
+/// Number of bytes in a FIFO, as far as the PL011 can tell.
+///
+/// The PL011 has no level registers. The flags give an exact count when a
+/// FIFO is empty or full. In between, a set raw RX interrupt means the RX
+/// FIFO is at or above its trigger level and a set raw TX interrupt means the
+/// TX FIFO is at or below its trigger level; otherwise only `1..depth` is
+/// known. While the FIFOs are disabled `depth` is 1 and the count is exact.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FifoFill {
+    pub min: u8,
+    pub max: u8,
+    pub depth: u8,
+}
+
+impl FifoFill {
+    pub fn is_exact(&self) -> bool {
+        self.min == self.max
+    }
+}
+
 /// UART peripheral identification structure
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Identification {
@@ -259,6 +364,8 @@ impl Identification {
     const DESIGNER_ARM: u8 = b'A';
     const REVISION_MAX: u8 = 0x03;
     const CONFIGURATION: u8 = 0x00;
+    /// First revision (r1p5) with 32-entry FIFOs.
+    const REVISION_R1P5: u8 = 0x03;
 
     /// Check if the identification block describes a valid PL011 peripheral
     pub fn is_valid(&self) -> bool {
@@ -267,13 +374,186 @@ impl Identification {
             && self.revision_number <= Self::REVISION_MAX
             && self.configuration == Self::CONFIGURATION
     }
+
+    /// Depth of the TX and RX FIFOs in bytes.
+    pub fn fifo_depth(&self) -> u8 {
+        if self.revision_number >= Self::REVISION_R1P5 {
+            32
+        } else {
+            16
+        }
+    }
+}
+
+/// Identity and live state of a PL011, see [`ArmPl011::dump_status`].
+#[derive(Clone, Copy, Debug)]
+pub struct StatusReport {
+    pub identification: Identification,
+    pub control: ControlRegister,
+    pub line_control: LineControlRegister,
+    pub flags: FlagsRegister,
+    pub interrupt_mask: Interrupts,
+    pub baudrate: u32,
+    /// Bytes in the RX FIFO at which the RX interrupt fires.
+    pub rx_trigger: u8,
+}
+
+impl fmt::Display for StatusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = &self.identification;
+        writeln!(
+            f,
+            "PL011 part {:#05x} designer {:#04x} rev {} ({})",
+            id.part_number,
+            id.designer,
+            id.revision_number,
+            if id.is_valid() { "valid" } else { "unknown" }
+        )?;
+
+        let lcr_h = self.line_control;
+        let data_bits = 5 + ((lcr_h.bits() >> 5) & 0b11);
+        let parity = match (
+            lcr_h.contains(LineControlRegister::PEN),
+            lcr_h.contains(LineControlRegister::SPS),
+            lcr_h.contains(LineControlRegister::EPS),
+        ) {
+            (false, _, _) => 'N',
+            (true, false, false) => 'O',
+            (true, false, true) => 'E',
+            (true, true, false) => 'M',
+            (true, true, true) => 'S',
+        };
+        let stop_bits = if lcr_h.contains(LineControlRegister::STP2) {
+            2
+        } else {
+            1
+        };
+        writeln!(
+            f,
+            "{} baud {}{}{}, fifo {}, rx trigger {}",
+            self.baudrate,
+            data_bits,
+            parity,
+            stop_bits,
+            if lcr_h.contains(LineControlRegister::FEN) {
+                "on"
+            } else {
+                "off"
+            },
+            self.rx_trigger
+        )?;
+
+        write!(f, "CR: ")?;
+        bitflags::parser::to_writer(&self.control, &mut *f)?;
+        write!(f, "\nFR: ")?;
+        bitflags::parser::to_writer(&self.flags, &mut *f)?;
+        write!(f, "\nIMSC: ")?;
+        bitflags::parser::to_writer(&self.interrupt_mask, &mut *f)
+    }
 }
 
 pub struct ArmPl011<'a> {
     pub regs: UnsafeCell<UniqueMmioPointer<'a, PL011Registers>>,
     pub sysclk: u32,
-    pub intr_handler: UnsafeCell<Option<&'static dyn Fn()>>,
+    pub intr_handler: UnsafeCell<Option<(fn(usize), usize)>>,
+    pub reset_ctrl: Option<(&'static dyn blueos_hal::reset::ResetCtrlWithDone, u32)>,
+    reset_pulse_us: u32,
+    rx_trigger: Option<FifoLevel>,
+    mmio_window: Option<(usize, usize)>,
+    rts_cts: bool,
+    irq: Option<u32>,
+    baud_tolerance: Option<u32>,
+    // Parameters of the last successful `configure`, for `recover`.
+    config: UnsafeCell<Option<super::UartConfig>>,
+    identification: UnsafeCell<Option<Identification>>,
+    // Interrupts enabled by `arm_wakeup` that were not enabled before.
+    wake_mask: UnsafeCell<Interrupts>,
+}
+
+/// Construction parameters for [`ArmPl011`].
+#[derive(Clone, Copy)]
+pub struct ArmPl011Config {
+    pub base_addr: usize,
+    pub sysclk: u32,
+    /// Reset controller and the reset line of this UART, pulsed on
+    /// `configure`.
     pub reset_ctrl: Option<(&'static dyn blueos_hal::reset::ResetCtrlWithDone, u32)>,
+    /// Minimum time the reset line is held, in microseconds. It is timed in
+    /// cycles of `sysclk`, which is the core clock on the boards using this.
+    pub reset_pulse_us: u32,
+    /// RX FIFO trigger level set by `configure`, instead of the one
+    /// [`rx_trigger_for_baud`] picks. A later `enable_fifo` sets both levels
+    /// again.
+    pub rx_trigger: Option<FifoLevel>,
+    /// Peripheral address range of the SoC, `start..end`. Debug builds
+    /// check on every register access that the register block lies inside
+    /// it, which catches a mistyped `base_addr`.
+    pub mmio_window: Option<(usize, usize)>,
+    /// nUARTCTS and nUARTRTS are routed to pins, so `configure` accepts
+    /// [`FlowCtrl::RtsCts`].
+    pub rts_cts: bool,
+    /// Interrupt line of this UART, reported by `get_irq_nums`.
+    pub irq: Option<u32>,
+    /// Largest deviation of the programmed baud rate from the requested
+    /// one that `configure` accepts, in 1/1000 of the requested rate. With
+    /// `None` a deviation above [`BAUD_RATE_TOLERANCE_PERMILLE`] is only
+    /// logged.
+    pub baud_tolerance: Option<u32>,
+}
+
+impl ArmPl011Config {
+    pub const fn new(base_addr: usize, sysclk: u32) -> Self {
+        Self {
+            base_addr,
+            sysclk,
+            reset_ctrl: None,
+            reset_pulse_us: 0,
+            rx_trigger: None,
+            mmio_window: None,
+            rts_cts: false,
+            irq: None,
+            baud_tolerance: None,
+        }
+    }
+
+    pub const fn with_reset(
+        mut self,
+        reset_ctrl: &'static dyn blueos_hal::reset::ResetCtrlWithDone,
+        line: u32,
+    ) -> Self {
+        self.reset_ctrl = Some((reset_ctrl, line));
+        self
+    }
+
+    pub const fn with_reset_pulse(mut self, min_us: u32) -> Self {
+        self.reset_pulse_us = min_us;
+        self
+    }
+
+    pub const fn with_rx_trigger(mut self, level: FifoLevel) -> Self {
+        self.rx_trigger = Some(level);
+        self
+    }
+
+    pub const fn with_mmio_window(mut self, start: usize, end: usize) -> Self {
+        self.mmio_window = Some((start, end));
+        self
+    }
+
+    pub const fn with_rts_cts(mut self) -> Self {
+        self.rts_cts = true;
+        self
+    }
+
+    pub const fn with_irq(mut self, irq: u32) -> Self {
+        self.irq = Some(irq);
+        self
+    }
+
+    pub const fn with_baud_tolerance(mut self, permille: u32) -> Self {
+        self.baud_tolerance = Some(permille);
+        self
+    }
 }
 
 impl ArmPl011<'_> {
@@ -282,13 +562,31 @@ impl ArmPl011<'_> {
         sysclk: u32,
         reset_ctrl: Option<(&'static dyn blueos_hal::reset::ResetCtrlWithDone, u32)>,
     ) -> Self {
+        Self::new_with(ArmPl011Config {
+            reset_ctrl,
+            ..ArmPl011Config::new(base_addr, sysclk)
+        })
+    }
+
+    pub const fn new_with(config: ArmPl011Config) -> Self {
         ArmPl011 {
             regs: UnsafeCell::new(unsafe {
-                UniqueMmioPointer::new(NonNull::new(base_addr as *mut PL011Registers).unwrap())
+                UniqueMmioPointer::new(
+                    NonNull::new(config.base_addr as *mut PL011Registers).unwrap(),
+                )
             }),
-            sysclk,
+            sysclk: config.sysclk,
             intr_handler: UnsafeCell::new(None),
-            reset_ctrl,
+            reset_ctrl: config.reset_ctrl,
+            reset_pulse_us: config.reset_pulse_us,
+            rx_trigger: config.rx_trigger,
+            mmio_window: config.mmio_window,
+            rts_cts: config.rts_cts,
+            irq: config.irq,
+            baud_tolerance: config.baud_tolerance,
+            config: UnsafeCell::new(None),
+            identification: UnsafeCell::new(None),
+            wake_mask: UnsafeCell::new(Interrupts::empty()),
         }
     }
 }
@@ -296,7 +594,7 @@ impl ArmPl011<'_> {
 /// Flag Register, UARTFR
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, Eq, FromBytes, Immutable, IntoBytes, KnownLayout, PartialEq)]
-struct FlagsRegister(u32);
+pub struct FlagsRegister(u32);
 
 macro_rules! field_used_by_inner {
     ($mmio_pointer:expr, $field:ident) => {{
@@ -313,20 +611,332 @@ macro_rules! field_used_by_inner {
     }};
 }
 
+impl ArmPl011<'static> {
+    /// Runs `f` with exclusive access to the register block.
+    ///
+    /// This is the only place that borrows `regs` mutably. It relies on the
+    /// users of the UART serializing their calls, as the serial layer does
+    /// with its lock, and on `f` not calling back into `self`.
+    fn with_regs<R>(
+        &self,
+        f: impl FnOnce(&mut UniqueMmioPointer<'static, PL011Registers>) -> R,
+    ) -> R {
+        // SAFETY: See above, no other reference to `regs` is live.
+        let regs = unsafe { &mut *self.regs.get() };
+        #[cfg(debug_assertions)]
+        self.check_mmio_window(regs.ptr_mut() as usize);
+        f(regs)
+    }
+
+    #[cfg(debug_assertions)]
+    fn check_mmio_window(&self, base: usize) {
+        let end = base.checked_add(core::mem::size_of::<PL011Registers>());
+        assert!(
+            base % 4 == 0 && end.is_some(),
+            "PL011 base {base:#x} is not a valid register block address"
+        );
+        if let Some((start, limit)) = self.mmio_window {
+            assert!(
+                base >= start && end.unwrap() <= limit,
+                "PL011 base {base:#x} is outside the peripheral window {start:#x}..{limit:#x}"
+            );
+        }
+    }
+
+    /// Reads the identification block from the PeriphID registers.
+    pub fn read_identification(&self) -> Identification {
+        let (id0, id1, id2, id3) = self.with_regs(|regs| {
+            (
+                field_used_by_inner!(regs, uartperiphid0).read(),
+                field_used_by_inner!(regs, uartperiphid1).read(),
+                field_used_by_inner!(regs, uartperiphid2).read(),
+                field_used_by_inner!(regs, uartperiphid3).read(),
+            )
+        });
+        Identification {
+            part_number: ((id0 & 0xFF) | ((id1 & 0x0F) << 8)) as u16,
+            designer: (((id1 >> 4) & 0x0F) | ((id2 & 0x0F) << 4)) as u8,
+            revision_number: ((id2 >> 4) & 0x0F) as u8,
+            configuration: (id3 & 0xFF) as u8,
+        }
+    }
+
+    /// Identification read by the last `configure`, `None` before that or if
+    /// the PeriphID registers don't describe a PL011.
+    pub fn identification(&self) -> Option<Identification> {
+        unsafe { *self.identification.get() }
+    }
+
+    /// Revision of the PL011 read by the last `configure`.
+    pub fn revision(&self) -> Option<u8> {
+        self.identification().map(|id| id.revision_number)
+    }
+
+    /// Baud rate actually programmed into the divisor registers, which may
+    /// differ from the requested one by the divisor rounding. Returns 0 if
+    /// no baud rate has been configured yet.
+    pub fn actual_baudrate(&self) -> u32 {
+        let (ibrd, fbrd) = self.with_regs(|regs| {
+            (
+                field_used_by_inner!(regs, uartibrd).read(),
+                field_used_by_inner!(regs, uartfbrd).read(),
+            )
+        });
+        baud_rate_from_divisor(ibrd, fbrd, self.sysclk)
+    }
+
+    /// Reads the identification and the current control, line control and
+    /// flag registers, for printing when debugging a port.
+    pub fn dump_status(&self) -> StatusReport {
+        let identification = self.read_identification();
+        let (control, line_control, flags, interrupt_mask, ifls) = self.with_regs(|regs| {
+            (
+                field_used_by_inner!(regs, uartcr).read(),
+                field_used_by_inner!(regs, uartlcr_h).read(),
+                field_used_by_inner!(regs, uartfr).read(),
+                field_used_by_inner!(regs, uartimsc).read(),
+                field_used_by_inner!(regs, uartifls).read(),
+            )
+        });
+        StatusReport {
+            identification,
+            control,
+            line_control,
+            flags,
+            interrupt_mask,
+            baudrate: self.actual_baudrate(),
+            rx_trigger: trigger_bytes(ifls >> 3, self.fifo_depth()),
+        }
+    }
+
+    /// Time to drain a full TX FIFO and the shift register at the programmed
+    /// baud rate, in microseconds, assuming the longest 12-bit frame.
+    pub fn drain_time_us(&self) -> u64 {
+        let baudrate = self.actual_baudrate() as u64;
+        if baudrate == 0 {
+            return 0;
+        }
+        (self.fifo_depth() as u64 + 1) * 12 * 1_000_000 / baudrate
+    }
+
+    /// Returns whether BUSY stays set for `timeout_us`, measured with the
+    /// microsecond clock `now_us`, e.g. because CTS is held off or the UART
+    /// is wedged. The caller can then re-initialize the port.
+    ///
+    /// The timeout is raised to [`drain_time_us`](Self::drain_time_us), so a
+    /// full FIFO on a slow link isn't mistaken for a stuck UART.
+    pub fn is_stuck(&self, timeout_us: u64, now_us: impl Fn() -> u64) -> bool {
+        let timeout_us = timeout_us.max(self.drain_time_us());
+        let start = now_us();
+        while self.is_bus_busy() {
+            if now_us().wrapping_sub(start) >= timeout_us {
+                return true;
+            }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// Sends a break, holding TX low for at least `duration_us` as measured
+    /// with the microsecond clock `now_us`.
+    ///
+    /// Setting BRK cuts the current character short, so this first waits
+    /// for the queued data to leave the line. Returns `Timeout` without
+    /// sending the break if that takes longer than
+    /// [`drain_time_us`](Self::drain_time_us), see [`is_stuck`](Self::is_stuck).
+    pub fn send_break(&self, duration_us: u64, now_us: impl Fn() -> u64) -> Result<()> {
+        if self.is_stuck(0, &now_us) {
+            return Err(HalError::Timeout);
+        }
+        self.with_regs(|regs| {
+            let lcr_h = field_used_by_inner!(regs, uartlcr_h).read();
+            field_used_by_inner!(regs, uartlcr_h).write(lcr_h | LineControlRegister::BRK);
+        });
+        spin_us(duration_us, &now_us);
+        self.with_regs(|regs| {
+            let lcr_h = field_used_by_inner!(regs, uartlcr_h).read();
+            field_used_by_inner!(regs, uartlcr_h).write(lcr_h - LineControlRegister::BRK);
+        });
+        Ok(())
+    }
+
+    /// Holds TX idle (mark) for `duration_us` as measured with `now_us`,
+    /// such as the mark after break of DMX512 or LIN. The next byte must
+    /// only be written once this returns.
+    ///
+    /// The time starts once the queued data has left the line, which is
+    /// bounded like in [`send_break`](Self::send_break). Mask interrupts
+    /// around the break, mark and first byte if the protocol has an upper
+    /// bound on the mark.
+    pub fn send_mark(&self, duration_us: u64, now_us: impl Fn() -> u64) -> Result<()> {
+        if self.is_stuck(0, &now_us) {
+            return Err(HalError::Timeout);
+        }
+        spin_us(duration_us, &now_us);
+        Ok(())
+    }
+
+    /// Resets the UART and re-applies the last configuration, for getting a
+    /// wedged port working again without re-running the board init.
+    ///
+    /// The FIFO trigger levels and the interrupt mask survive the reset.
+    /// Returns `NotReady` if the UART has never been configured.
+    pub fn recover(&self) -> Result<()> {
+        let config = unsafe { (*self.config.get()).clone() }.ok_or(HalError::NotReady)?;
+        let (imsc, ifls, lcr_h) = self.with_regs(|regs| {
+            (
+                field_used_by_inner!(regs, uartimsc).read(),
+                field_used_by_inner!(regs, uartifls).read(),
+                field_used_by_inner!(regs, uartlcr_h).read(),
+            )
+        });
+        self.configure(&config)?;
+        self.with_regs(|regs| {
+            field_used_by_inner!(regs, uartifls).write(ifls);
+            if lcr_h.contains(LineControlRegister::FEN) {
+                let lcr_h = field_used_by_inner!(regs, uartlcr_h).read();
+                field_used_by_inner!(regs, uartlcr_h).write(lcr_h | LineControlRegister::FEN);
+            }
+            field_used_by_inner!(regs, uarticr).write(ALL_INTERRUPTS);
+            field_used_by_inner!(regs, uartimsc).write(imsc);
+        });
+        Ok(())
+    }
+
+    /// Errors latched since the last [`clear_rx_status`](Self::clear_rx_status)
+    /// or `configure`. Unlike the error bits read with each byte, these
+    /// accumulate, so polling them now and then tells how noisy the link
+    /// has been.
+    ///
+    /// The break, parity and framing bits are only latched when the
+    /// offending character is read from the FIFO.
+    pub fn rx_error_status(&self) -> RxErrors {
+        let rsr = self.with_regs(|regs| field_used_by_inner!(regs, uartrsr_ecr).read());
+        let rsr = ReceiveStatusRegister::from_bits_truncate(rsr);
+        RxErrors {
+            overrun: rsr.contains(ReceiveStatusRegister::OE),
+            break_: rsr.contains(ReceiveStatusRegister::BE),
+            parity: rsr.contains(ReceiveStatusRegister::PE),
+            framing: rsr.contains(ReceiveStatusRegister::FE),
+        }
+    }
+
+    /// Clears the latched receive errors.
+    pub fn clear_rx_status(&self) {
+        // Any write to UARTECR clears all of them.
+        self.with_regs(|regs| field_used_by_inner!(regs, uartrsr_ecr).write(0));
+    }
+
+    /// Hardware flow control in effect. With RTS/CTS the UART stops sending
+    /// while CTS is deasserted and deasserts RTS while the RX FIFO is full.
+    pub fn flow_control(&self) -> FlowCtrl {
+        let cr = self.with_regs(|regs| field_used_by_inner!(regs, uartcr).read());
+        if cr.contains(ControlRegister::RTSEn | ControlRegister::CTSEn) {
+            FlowCtrl::RtsCts
+        } else {
+            FlowCtrl::None
+        }
+    }
+
+    /// Calls the installed interrupt handler, if any.
+    pub fn handle_interrupt(&self) {
+        if let Some((handler, ctx)) = unsafe { *self.intr_handler.get() } {
+            handler(ctx);
+        }
+    }
+
+    /// FIFO depth of this instance. Unknown revisions are assumed to have the
+    /// deeper r1p5 FIFO, matching the historical trigger level mapping.
+    fn fifo_depth(&self) -> u8 {
+        self.identification().map_or(32, |id| id.fifo_depth())
+    }
+
+    /// Bounds on the number of bytes waiting in the RX FIFO.
+    pub fn rx_fifo_level(&self) -> FifoFill {
+        let (flags, ris, ifls, depth) = self.fifo_state();
+        let (min, max) = if flags.contains(FlagsRegister::RXFE) {
+            (0, 0)
+        } else if flags.contains(FlagsRegister::RXFF) {
+            (depth, depth)
+        } else if ris.contains(Interrupts::RXI) {
+            // The raw RX interrupt stays set until reads take the FIFO below
+            // the trigger level, unless it is cleared in UARTICR.
+            (trigger_bytes(ifls >> 3, depth).max(1), depth - 1)
+        } else {
+            (1, depth - 1)
+        };
+        FifoFill { min, max, depth }
+    }
+
+    /// Bounds on the number of bytes waiting in the TX FIFO.
+    pub fn tx_fifo_level(&self) -> FifoFill {
+        let (flags, ris, ifls, depth) = self.fifo_state();
+        let (min, max) = if flags.contains(FlagsRegister::TXFE) {
+            (0, 0)
+        } else if flags.contains(FlagsRegister::TXFF) {
+            (depth, depth)
+        } else if ris.contains(Interrupts::TXI) {
+            (1, trigger_bytes(ifls, depth).min(depth - 1).max(1))
+        } else {
+            (1, depth - 1)
+        };
+        FifoFill { min, max, depth }
+    }
+
+    /// Flags, raw interrupt status, UARTIFLS and the effective FIFO depth.
+    fn fifo_state(&self) -> (FlagsRegister, Interrupts, u32, u8) {
+        let (flags, lcr_h, ris, ifls) = self.with_regs(|regs| {
+            (
+                field_used_by_inner!(regs, uartfr).read(),
+                field_used_by_inner!(regs, uartlcr_h).read(),
+                field_used_by_inner!(regs, uartris).read(),
+                field_used_by_inner!(regs, uartifls).read(),
+            )
+        });
+        // Without FEN the FIFOs act as one-byte holding registers.
+        let depth = if lcr_h.contains(LineControlRegister::FEN) {
+            self.fifo_depth()
+        } else {
+            1
+        };
+        (flags, ris, ifls, depth)
+    }
+}
+
 impl Configuration<super::UartConfig> for ArmPl011<'static> {
     type Target = ();
     fn configure(&self, param: &super::UartConfig) -> blueos_hal::err::Result<Self::Target> {
         if let Some(ref reset_ctrl) = self.reset_ctrl {
             let (reset_ctrl, reset_id) = reset_ctrl;
-            reset_ctrl.set_reset(*reset_id);
-            reset_ctrl.clear_reset(*reset_id);
-            reset_ctrl.wait_done(*reset_id);
+            reset_ctrl.reset_pulse(*reset_id, self.reset_pulse_us, self.sysclk);
         }
 
+        let id = self.read_identification();
+        log::info!(
+            "PL011 part {:#05x} designer {:#04x} rev {}",
+            id.part_number,
+            id.designer,
+            id.revision_number
+        );
+        unsafe { *self.identification.get() = id.is_valid().then_some(id) };
+
         // Baud rate
         let (uartibrd, uartfbrd) = calculate_baud_rate_divisor(param.baudrate, self.sysclk)?;
+        let actual = baud_rate_from_divisor(uartibrd, uartfbrd, self.sysclk);
+        let exceeds = |permille: u32| {
+            actual.abs_diff(param.baudrate) as u64 * 1000 > param.baudrate as u64 * permille as u64
+        };
+        match self.baud_tolerance {
+            Some(permille) if exceeds(permille) => return Err(HalError::InvalidParam),
+            None if exceeds(BAUD_RATE_TOLERANCE_PERMILLE) => log::warn!(
+                "PL011 baud rate {} programmed as {}",
+                param.baudrate,
+                actual
+            ),
+            _ => {}
+        }
 
-        let line_control = match param.data_bits {
+        let mut line_control = match param.data_bits {
             DataBits::DataBits8 => LineControlRegister::WLEN_8BITS,
             DataBits::DataBits7 => LineControlRegister::WLEN_7BITS,
             DataBits::DataBits6 => LineControlRegister::WLEN_6BITS,
@@ -336,17 +946,56 @@ impl Configuration<super::UartConfig> for ArmPl011<'static> {
             }
         };
 
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        field_used_by_inner!(unsafe_mut_ref, uartrsr_ecr).write(0);
-        field_used_by_inner!(unsafe_mut_ref, uartcr).write(ControlRegister::empty());
+        // STP2 sends two stop bits at any word length, there is no 1.5.
+        match param.stop_bits {
+            StopBits::One => {}
+            StopBits::Two => line_control |= LineControlRegister::STP2,
+            _ => return Err(HalError::InvalidParam),
+        }
+
+        // With SPS the parity bit is fixed, to 1 if EPS is clear and to 0
+        // if it is set.
+        line_control |= match param.parity {
+            Parity::None => LineControlRegister::empty(),
+            Parity::Odd => LineControlRegister::PEN,
+            Parity::Even => LineControlRegister::PEN | LineControlRegister::EPS,
+            Parity::Mark => LineControlRegister::PEN | LineControlRegister::SPS,
+            Parity::Space => {
+                LineControlRegister::PEN | LineControlRegister::EPS | LineControlRegister::SPS
+            }
+        };
+
+        // The PL011 has no DTR/DSR handshake or RS-485 direction control.
+        let flow_control = match param.flow_ctrl {
+            FlowCtrl::None => ControlRegister::empty(),
+            FlowCtrl::RtsCts if self.rts_cts => ControlRegister::RTSEn | ControlRegister::CTSEn,
+            _ => return Err(HalError::NotSupport),
+        };
+
+        let rx_trigger = self
+            .rx_trigger
+            .unwrap_or(rx_trigger_for_baud(param.baudrate));
 
-        field_used_by_inner!(unsafe_mut_ref, uartibrd).write(uartibrd);
-        field_used_by_inner!(unsafe_mut_ref, uartfbrd).write(uartfbrd);
-        field_used_by_inner!(unsafe_mut_ref, uartlcr_h).write(line_control);
+        self.with_regs(|regs| {
+            field_used_by_inner!(regs, uartrsr_ecr).write(0);
+            field_used_by_inner!(regs, uartcr).write(ControlRegister::empty());
 
-        field_used_by_inner!(unsafe_mut_ref, uartcr)
-            .write(ControlRegister::RXE | ControlRegister::TXE | ControlRegister::UARTEN);
+            field_used_by_inner!(regs, uartibrd).write(uartibrd);
+            field_used_by_inner!(regs, uartfbrd).write(uartfbrd);
+            field_used_by_inner!(regs, uartlcr_h).write(line_control);
+            // Keep the TX level, only the RX side depends on the baud rate.
+            let ifls = field_used_by_inner!(regs, uartifls).read();
+            field_used_by_inner!(regs, uartifls).write((ifls & 0b111) | ((rx_trigger as u32) << 3));
 
+            field_used_by_inner!(regs, uartcr).write(
+                ControlRegister::RXE
+                    | ControlRegister::TXE
+                    | ControlRegister::UARTEN
+                    | flow_control,
+            );
+        });
+
+        unsafe { *self.config.get() = Some(param.clone()) };
         Ok(())
     }
 }
@@ -354,12 +1003,35 @@ impl Configuration<super::UartConfig> for ArmPl011<'static> {
 impl Uart<super::UartConfig, (), super::InterruptType, super::UartCtrlStatus>
     for ArmPl011<'static>
 {
+    fn get_ctrl_status(&self) -> Result<super::UartCtrlStatus> {
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
+        Ok(super::UartCtrlStatus {
+            cts: flags.contains(FlagsRegister::CTS),
+            dsr: flags.contains(FlagsRegister::DSR),
+            dcd: flags.contains(FlagsRegister::DCD),
+            ri: flags.contains(FlagsRegister::RI),
+            busy: flags.contains(FlagsRegister::BUSY),
+            rts_cts: self.flow_control() == FlowCtrl::RtsCts,
+            rx_errors: self.rx_error_status(),
+            tx_fifo_empty: flags.contains(FlagsRegister::TXFE),
+            tx_fifo_full: flags.contains(FlagsRegister::TXFF),
+            rx_fifo_empty: flags.contains(FlagsRegister::RXFE),
+            rx_fifo_full: flags.contains(FlagsRegister::RXFF),
+        })
+    }
 }
 
 impl Has8bitDataReg for ArmPl011<'static> {
     fn read_data8(&self) -> Result<u8> {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let data_reg = field_used_by_inner!(unsafe_mut_ref, uartdr).read();
+        let data_reg = self
+            .with_regs(|regs| {
+                let cr = field_used_by_inner!(regs, uartcr).read();
+                // The data register of a UART that isn't configured and
+                // enabled holds junk, report that instead of returning it.
+                cr.contains(ControlRegister::UARTEN | ControlRegister::RXE)
+                    .then(|| field_used_by_inner!(regs, uartdr).read())
+            })
+            .ok_or(HalError::NotReady)?;
 
         let flags = DataRegister::from_bits_truncate(data_reg);
 
@@ -375,115 +1047,117 @@ impl Has8bitDataReg for ArmPl011<'static> {
     }
 
     fn write_data8(&self, data: u8) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        field_used_by_inner!(unsafe_mut_ref, uartdr).write(data as u32);
+        self.with_regs(|regs| field_used_by_inner!(regs, uartdr).write(data as u32));
     }
 
     fn is_data_ready(&self) -> bool {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let flags = field_used_by_inner!(unsafe_mut_ref, uartfr).read();
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
         !flags.contains(FlagsRegister::RXFE)
     }
 }
 
 impl HasLineStatusReg for ArmPl011<'static> {
     fn is_bus_busy(&self) -> bool {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let flags = field_used_by_inner!(unsafe_mut_ref, uartfr).read();
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
         flags.contains(FlagsRegister::BUSY)
     }
 }
 
 impl HasFifo for ArmPl011<'static> {
     fn enable_fifo(&self, num: u8) -> Result<()> {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let ifls_value = match num {
-            4 => FifoLevel::Bytes4 as u32,
-            8 => FifoLevel::Bytes8 as u32,
-            16 => FifoLevel::Bytes16 as u32,
-            24 => FifoLevel::Bytes24 as u32,
-            28 => FifoLevel::Bytes28 as u32,
+        // Trigger levels are in eighths of the FIFO, which is only 16 bytes
+        // deep before r1p5.
+        let depth = self.fifo_depth() as u32;
+        let eighths = num as u32 * 8;
+        if eighths % depth != 0 {
+            return Err(HalError::InvalidParam);
+        }
+        let ifls_value = match eighths / depth {
+            1 => FifoLevel::Bytes4 as u32,
+            2 => FifoLevel::Bytes8 as u32,
+            4 => FifoLevel::Bytes16 as u32,
+            6 => FifoLevel::Bytes24 as u32,
+            7 => FifoLevel::Bytes28 as u32,
             _ => return Err(HalError::InvalidParam),
         };
 
         // Set RX and TX FIFO levels
         let ifls_reg = (ifls_value << 3) | ifls_value;
-        field_used_by_inner!(unsafe_mut_ref, uartifls).write(ifls_reg);
+        self.with_regs(|regs| {
+            field_used_by_inner!(regs, uartifls).write(ifls_reg);
 
-        // Enable FIFOs
-        let mut lcr_h = field_used_by_inner!(unsafe_mut_ref, uartlcr_h).read();
-        lcr_h |= LineControlRegister::FEN;
-        field_used_by_inner!(unsafe_mut_ref, uartlcr_h).write(lcr_h);
+            // Enable FIFOs
+            let mut lcr_h = field_used_by_inner!(regs, uartlcr_h).read();
+            lcr_h |= LineControlRegister::FEN;
+            field_used_by_inner!(regs, uartlcr_h).write(lcr_h);
+        });
 
         Ok(())
     }
 
     fn is_tx_fifo_full(&self) -> bool {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let flags = field_used_by_inner!(unsafe_mut_ref, uartfr).read();
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
         flags.contains(FlagsRegister::TXFF)
     }
 
+    fn is_tx_fifo_empty(&self) -> bool {
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
+        flags.contains(FlagsRegister::TXFE)
+    }
+
     fn is_rx_fifo_empty(&self) -> bool {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let flags = field_used_by_inner!(unsafe_mut_ref, uartfr).read();
+        let flags = self.with_regs(|regs| field_used_by_inner!(regs, uartfr).read());
         flags.contains(FlagsRegister::RXFE)
     }
+
+    fn tx_fifo_space(&self) -> usize {
+        let fill = self.tx_fifo_level();
+        (fill.depth - fill.max) as usize
+    }
+
+    fn rx_fifo_count(&self) -> usize {
+        self.rx_fifo_level().min as usize
+    }
 }
 
 impl HasInterruptReg for ArmPl011<'static> {
     type InterruptType = super::InterruptType;
 
     fn enable_interrupt(&self, intr: Self::InterruptType) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let mut imsc = field_used_by_inner!(unsafe_mut_ref, uartimsc).read();
-        match intr {
-            super::InterruptType::Tx => {
-                imsc |= Interrupts::TXI;
-            }
-            super::InterruptType::Rx => {
-                imsc |= Interrupts::RXI;
-            }
-            _ => {}
-        }
-        field_used_by_inner!(unsafe_mut_ref, uartimsc).write(imsc);
+        self.with_regs(|regs| {
+            let imsc = field_used_by_inner!(regs, uartimsc).read();
+            field_used_by_inner!(regs, uartimsc).write(imsc | interrupt_mask(&intr));
+        });
     }
 
     fn disable_interrupt(&self, intr: Self::InterruptType) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let mut imsc = field_used_by_inner!(unsafe_mut_ref, uartimsc).read();
-        match intr {
-            super::InterruptType::Tx => {
-                imsc &= !Interrupts::TXI;
-            }
-            super::InterruptType::Rx => {
-                imsc &= !Interrupts::RXI;
-            }
-            _ => {}
-        }
-        imsc &= !Interrupts::from_bits_truncate(intr as u32);
-        field_used_by_inner!(unsafe_mut_ref, uartimsc).write(imsc);
+        self.with_regs(|regs| {
+            let imsc = field_used_by_inner!(regs, uartimsc).read();
+            field_used_by_inner!(regs, uartimsc).write(imsc - interrupt_mask(&intr));
+        });
     }
 
+    /// Clearing `Error` or `All` also clears the receive errors latched in
+    /// UARTRSR, see [`ArmPl011::rx_error_status`].
     fn clear_interrupt(&self, intr: Self::InterruptType) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        match intr {
-            super::InterruptType::Tx => {
-                field_used_by_inner!(unsafe_mut_ref, uarticr).write(Interrupts::TXI);
+        let mask = interrupt_mask(&intr);
+        self.with_regs(|regs| {
+            field_used_by_inner!(regs, uarticr).write(mask);
+            if mask.intersects(RX_ERRORS) {
+                field_used_by_inner!(regs, uartrsr_ecr).write(0);
             }
-            super::InterruptType::Rx => {
-                field_used_by_inner!(unsafe_mut_ref, uarticr).write(Interrupts::RXI);
-            }
-            _ => {}
-        }
+        });
     }
 
     fn get_interrupt(&self) -> Self::InterruptType {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let mis = field_used_by_inner!(unsafe_mut_ref, uartmis).read();
+        let mis = self.with_regs(|regs| field_used_by_inner!(regs, uartmis).read());
 
-        if mis.contains(Interrupts::RXI) {
+        if mis.intersects(RX_ERRORS) {
+            super::InterruptType::Error
+        } else if mis.contains(Interrupts::RXI) {
             super::InterruptType::Rx
+        } else if mis.contains(Interrupts::RTI) {
+            super::InterruptType::RxTimeout
         } else if mis.contains(Interrupts::TXI) {
             super::InterruptType::Tx
         } else {
@@ -491,13 +1165,63 @@ impl HasInterruptReg for ArmPl011<'static> {
         }
     }
 
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn()) {
+    /// A mask holding only the receive timeout or only the error
+    /// interrupts reads back as that kind. Anything else is folded into its
+    /// receive side (RXI, RTI and the errors) and TXI.
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        let imsc = self.with_regs(|regs| field_used_by_inner!(regs, uartimsc).read());
+        let rx = Interrupts::RXI.union(Interrupts::RTI).union(RX_ERRORS);
+        let imsc = imsc.intersection(rx.union(Interrupts::TXI));
+        if imsc == Interrupts::RTI {
+            super::InterruptType::RxTimeout
+        } else if imsc == RX_ERRORS {
+            super::InterruptType::Error
+        } else {
+            super::InterruptType::from_enabled(imsc.intersects(rx), imsc.contains(Interrupts::TXI))
+        }
+    }
+
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize) {
         let intr_handler_cell = unsafe { &mut *self.intr_handler.get() };
-        *intr_handler_cell = Some(handler);
+        *intr_handler_cell = Some((handler, ctx));
     }
 
     fn get_irq_nums(&self) -> &[u32] {
-        &[]
+        self.irq.as_slice()
+    }
+}
+
+impl HandlesIrq for ArmPl011<'static> {
+    fn handle_irq(&self) {
+        let intr = self.get_interrupt();
+        self.handle_interrupt();
+        self.clear_interrupt(intr);
+    }
+}
+
+impl WakeSource for ArmPl011<'static> {
+    // Any received byte wakes the CPU. The receive timeout covers a lone byte
+    // that stays below the RX FIFO trigger level.
+    fn arm_wakeup(&self) {
+        self.with_regs(|regs| {
+            let imsc = field_used_by_inner!(regs, uartimsc).read();
+            let wake = Interrupts::RXI | Interrupts::RTI;
+            unsafe { *self.wake_mask.get() = wake - imsc };
+            field_used_by_inner!(regs, uartimsc).write(imsc | wake);
+        });
+    }
+
+    fn disarm_wakeup(&self) {
+        let added = unsafe { core::mem::replace(&mut *self.wake_mask.get(), Interrupts::empty()) };
+        self.with_regs(|regs| {
+            let imsc = field_used_by_inner!(regs, uartimsc).read();
+            field_used_by_inner!(regs, uartimsc).write(imsc - added);
+        });
+    }
+
+    fn is_wake_cause(&self) -> bool {
+        let ris = self.with_regs(|regs| field_used_by_inner!(regs, uartris).read());
+        ris.intersects(Interrupts::RXI | Interrupts::RTI)
     }
 }
 
@@ -506,20 +1230,34 @@ unsafe impl Send for ArmPl011<'static> {}
 
 impl PlatPeri for ArmPl011<'static> {
     fn enable(&self) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let mut cr = field_used_by_inner!(unsafe_mut_ref, uartcr).read();
-        cr |= ControlRegister::UARTEN | ControlRegister::RXE | ControlRegister::TXE;
-        field_used_by_inner!(unsafe_mut_ref, uartcr).write(cr);
+        self.with_regs(|regs| {
+            let mut cr = field_used_by_inner!(regs, uartcr).read();
+            cr |= ControlRegister::UARTEN | ControlRegister::RXE | ControlRegister::TXE;
+            field_used_by_inner!(regs, uartcr).write(cr);
+        });
     }
 
     fn disable(&self) {
-        let unsafe_mut_ref = unsafe { &mut *self.regs.get() };
-        let mut cr = field_used_by_inner!(unsafe_mut_ref, uartcr).read();
-        cr &= !(ControlRegister::UARTEN);
-        field_used_by_inner!(unsafe_mut_ref, uartcr).write(cr);
+        self.with_regs(|regs| {
+            let mut cr = field_used_by_inner!(regs, uartcr).read();
+            cr &= !(ControlRegister::UARTEN);
+            field_used_by_inner!(regs, uartcr).write(cr);
+        });
     }
 }
 
+fn spin_us(duration_us: u64, now_us: &impl Fn() -> u64) {
+    let start = now_us();
+    while now_us().wrapping_sub(start) < duration_us {
+        core::hint::spin_loop();
+    }
+}
+
+/// Deviation of the programmed baud rate from the requested one above which
+/// `configure` warns, in 1/1000 of the requested rate, unless
+/// [`ArmPl011Config::baud_tolerance`] makes it fail instead.
+pub const BAUD_RATE_TOLERANCE_PERMILLE: u32 = 20;
+
 fn calculate_baud_rate_divisor(baud_rate: u32, sysclk: u32) -> Result<(u32, u32)> {
     // baud_div = sysclk / (baud_rate * 16)
     // baud_div_bits = (baud_div * 2^7 + 1) / 2
@@ -543,3 +1281,13 @@ fn calculate_baud_rate_divisor(baud_rate: u32, sysclk: u32) -> Result<(u32, u32)
 
     Ok((ibrd, fbrd))
 }
+
+fn baud_rate_from_divisor(ibrd: u32, fbrd: u32, sysclk: u32) -> u32 {
+    // baud_rate = sysclk / (16 * (ibrd + fbrd / 64))
+    //           = sysclk * 4 / (ibrd * 64 + fbrd)
+    let div = ((ibrd as u64) << 6) | (fbrd as u64 & 0x3F);
+    if div == 0 {
+        return 0;
+    }
+    ((sysclk as u64 * 4 + div / 2) / div) as u32
+}
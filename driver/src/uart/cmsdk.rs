@@ -107,7 +107,7 @@ register_structs! {
 pub struct Cmsdk {
     registers: *mut Registers,
     clk: u32,
-    pub intr_handler: UnsafeCell<Option<&'static dyn Fn()>>,
+    pub intr_handler: UnsafeCell<Option<(fn(usize), usize)>>,
     irq_nums: [u32; 2],
 }
 
@@ -127,6 +127,13 @@ impl Cmsdk {
         // appropriately mapped, as promised by the caller of `Uart::new`.
         unsafe { &(*self.registers) }
     }
+
+    /// Calls the installed interrupt handler, if any.
+    pub fn handle_interrupt(&self) {
+        if let Some((handler, ctx)) = unsafe { *self.intr_handler.get() } {
+            handler(ctx);
+        }
+    }
 }
 
 unsafe impl Send for Cmsdk {}
@@ -135,6 +142,11 @@ unsafe impl Sync for Cmsdk {}
 impl Configuration<super::UartConfig> for Cmsdk {
     type Target = ();
     fn configure(&self, param: &super::UartConfig) -> blueos_hal::err::Result<Self::Target> {
+        // The CMSDK UART has no modem control lines.
+        if param.flow_ctrl != super::FlowCtrl::None {
+            return Err(blueos_hal::err::HalError::NotSupport);
+        }
+
         let divisor = (self.clk << 2) / param.baudrate;
 
         self.registers().BAUDDIV.set(divisor);
@@ -189,6 +201,10 @@ impl HasFifo for Cmsdk {
     fn is_rx_fifo_empty(&self) -> bool {
         !self.registers().STATE.is_set(STATE::RXBF)
     }
+
+    fn is_tx_fifo_empty(&self) -> bool {
+        !self.registers().STATE.is_set(STATE::TXBF)
+    }
 }
 
 impl HasInterruptReg for Cmsdk {
@@ -245,9 +261,14 @@ impl HasInterruptReg for Cmsdk {
         }
     }
 
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn()) {
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        let ctrl = self.registers().CTRL.extract();
+        super::InterruptType::from_enabled(ctrl.is_set(CTRL::RXIRQEN), ctrl.is_set(CTRL::TXIRQEN))
+    }
+
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize) {
         unsafe {
-            *self.intr_handler.get() = Some(handler);
+            *self.intr_handler.get() = Some((handler, ctx));
         }
     }
 
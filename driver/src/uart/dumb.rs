@@ -62,6 +62,10 @@ impl HasFifo for DumbUart {
     fn is_rx_fifo_empty(&self) -> bool {
         false
     }
+
+    fn is_tx_fifo_empty(&self) -> bool {
+        true
+    }
 }
 
 impl HasInterruptReg for DumbUart {
@@ -83,8 +87,12 @@ impl HasInterruptReg for DumbUart {
         super::InterruptType::Unknown
     }
 
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn()) {
-        let _ = handler;
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        super::InterruptType::Unknown
+    }
+
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize) {
+        let _ = (handler, ctx);
     }
 
     fn get_irq_nums(&self) -> &[u32] {
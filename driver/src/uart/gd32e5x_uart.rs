@@ -25,7 +25,7 @@ pub struct Gd32e5xUart {
     // but the situation may be different for other UART peripherals
     inner: u32,
     clk: u32,
-    pub intr_handler: UnsafeCell<Option<&'static dyn Fn()>>,
+    pub intr_handler: UnsafeCell<Option<(fn(usize), usize)>>,
     reset_id: u32,
     reset: &'static dyn ResetCtrl,
 }
@@ -44,6 +44,11 @@ impl Configuration<super::UartConfig> for Gd32e5xUart {
             flow_ctrl,
         } = param;
 
+        // The CTS/RTS enables in CTL2 are not programmed.
+        if *flow_ctrl != super::FlowCtrl::None {
+            return Err(blueos_hal::err::HalError::NotSupport);
+        }
+
         self.reset.toggle(self.reset_id);
 
         let baudrate_div = self.clk / *baudrate;
@@ -75,16 +80,16 @@ impl Configuration<super::UartConfig> for Gd32e5xUart {
             }
 
             match stop_bits {
-                StopBits::DataBits0_5 => {
+                StopBits::Half => {
                     self.regs().ctl1().modify(|_, w| w.stb().bits(0b01));
                 }
-                StopBits::DataBits1 => {
+                StopBits::One => {
                     self.regs().ctl1().modify(|_, w| w.stb().bits(0b00));
                 }
-                StopBits::DataBits1_5 => {
+                StopBits::OnePointFive => {
                     self.regs().ctl1().modify(|_, w| w.stb().bits(0b11));
                 }
-                StopBits::DataBits2 => {
+                StopBits::Two => {
                     self.regs().ctl1().modify(|_, w| w.stb().bits(0b10));
                 }
             }
@@ -120,6 +125,13 @@ impl Gd32e5xUart {
     fn regs(&self) -> &usart0::RegisterBlock {
         unsafe { &*(self.inner as *const usart0::RegisterBlock) }
     }
+
+    /// Calls the installed interrupt handler, if any.
+    pub fn handle_interrupt(&self) {
+        if let Some((handler, ctx)) = unsafe { *self.intr_handler.get() } {
+            handler(ctx);
+        }
+    }
 }
 
 impl Uart<super::UartConfig, (), super::InterruptType, super::UartCtrlStatus> for Gd32e5xUart {}
@@ -165,6 +177,10 @@ impl HasFifo for Gd32e5xUart {
     fn is_rx_fifo_empty(&self) -> bool {
         self.regs().stat0().read().rbne().bit_is_clear()
     }
+
+    fn is_tx_fifo_empty(&self) -> bool {
+        self.regs().stat0().read().tbe().bit_is_set()
+    }
 }
 
 impl HasInterruptReg for Gd32e5xUart {
@@ -221,9 +237,17 @@ impl HasInterruptReg for Gd32e5xUart {
         }
     }
 
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn()) {
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        let ctl0 = self.regs().ctl0().read();
+        super::InterruptType::from_enabled(
+            ctl0.rbneie().bit_is_set(),
+            ctl0.tcie().bit_is_set() || ctl0.tbeie().bit_is_set(),
+        )
+    }
+
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize) {
         unsafe {
-            *self.intr_handler.get() = Some(handler);
+            *self.intr_handler.get() = Some((handler, ctx));
         }
     }
 
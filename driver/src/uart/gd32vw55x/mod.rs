@@ -195,16 +195,16 @@ impl Configuration<super::UartConfig> for Gd32vw55xUart {
                 _ => return Err(blueos_hal::err::HalError::NotSupport),
             }
             match stop_bits {
-                super::StopBits::DataBits0_5 => {
+                super::StopBits::Half => {
                     usart_stop_bit_set(self.base_addr, USART_STB_0_5BIT);
                 }
-                super::StopBits::DataBits1 => {
+                super::StopBits::One => {
                     usart_stop_bit_set(self.base_addr, USART_STB_1BIT);
                 }
-                super::StopBits::DataBits1_5 => {
+                super::StopBits::OnePointFive => {
                     usart_stop_bit_set(self.base_addr, USART_STB_1_5BIT);
                 }
-                super::StopBits::DataBits2 => {
+                super::StopBits::Two => {
                     usart_stop_bit_set(self.base_addr, USART_STB_2BIT);
                 }
             }
@@ -277,6 +277,10 @@ impl HasFifo for Gd32vw55xUart {
     fn is_rx_fifo_empty(&self) -> bool {
         unsafe { usart_flag_get(self.base_addr, UsartFlag::RFE as u32) == 1 }
     }
+
+    fn is_tx_fifo_empty(&self) -> bool {
+        unsafe { usart_flag_get(self.base_addr, UsartFlag::TBE as u32) == 1 }
+    }
 }
 
 impl HasInterruptReg for Gd32vw55xUart {
@@ -292,7 +296,11 @@ impl HasInterruptReg for Gd32vw55xUart {
         super::InterruptType::Unknown
     }
 
-    fn set_interrupt_handler(&self, _handler: &'static dyn Fn()) {}
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        super::InterruptType::Unknown
+    }
+
+    fn set_interrupt_handler(&self, _handler: fn(usize), _ctx: usize) {}
 
     fn get_irq_nums(&self) -> &[u32] {
         &[]
@@ -34,10 +34,10 @@ pub enum Parity {
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[repr(C)]
 pub enum StopBits {
-    DataBits0_5 = 0,
-    DataBits1 = 1,
-    DataBits1_5 = 2,
-    DataBits2 = 3,
+    Half = 0,
+    One = 1,
+    OnePointFive = 2,
+    Two = 3,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,6 +63,7 @@ pub enum FlowCtrl {
     Rs485 = 3,
 }
 
+#[derive(Clone)]
 pub struct UartConfig {
     pub baudrate: u32,
     pub parity: Parity,
@@ -76,7 +77,7 @@ impl Default for UartConfig {
         UartConfig {
             baudrate: 115200,
             parity: Parity::None,
-            stop_bits: StopBits::DataBits1,
+            stop_bits: StopBits::One,
             data_bits: DataBits::DataBits8,
             flow_ctrl: FlowCtrl::None,
         }
@@ -89,14 +90,63 @@ pub enum InterruptType {
     All,
     Rx,
     Tx,
+    /// Data has been waiting in the RX FIFO without reaching the trigger
+    /// level.
+    RxTimeout,
+    /// An overrun, break, parity or framing error on reception.
+    Error,
     Unknown,
 }
 
-#[non_exhaustive]
-pub enum UartCtrlStatus {
-    OverrunError,
-    BreakError,
-    ParityError,
-    FramingError,
-    Normal,
+impl InterruptType {
+    /// Folds the RX and TX enable bits of an interrupt mask, `Unknown`
+    /// meaning neither is enabled.
+    pub(crate) fn from_enabled(rx: bool, tx: bool) -> Self {
+        match (rx, tx) {
+            (true, true) => Self::All,
+            (true, false) => Self::Rx,
+            (false, true) => Self::Tx,
+            (false, false) => Self::Unknown,
+        }
+    }
+}
+
+/// Receive errors latched since they were last cleared, see
+/// [`ArmPl011::rx_error_status`](arm_pl011::ArmPl011::rx_error_status).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct RxErrors {
+    /// Data arrived while the RX FIFO was full and was lost.
+    pub overrun: bool,
+    /// RX was held low for longer than a whole frame.
+    pub break_: bool,
+    pub parity: bool,
+    /// A character had no valid stop bit.
+    pub framing: bool,
+}
+
+impl RxErrors {
+    pub fn any(&self) -> bool {
+        self.overrun || self.break_ || self.parity || self.framing
+    }
+}
+
+/// Line state of a UART, returned by `Uart::get_ctrl_status`. The modem
+/// lines read `true` while asserted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[repr(C)]
+pub struct UartCtrlStatus {
+    pub cts: bool,
+    pub dsr: bool,
+    pub dcd: bool,
+    pub ri: bool,
+    /// A character is being sent.
+    pub busy: bool,
+    /// RTS/CTS flow control is in effect.
+    pub rts_cts: bool,
+    pub rx_errors: RxErrors,
+    pub tx_fifo_empty: bool,
+    pub tx_fifo_full: bool,
+    pub rx_fifo_empty: bool,
+    pub rx_fifo_full: bool,
 }
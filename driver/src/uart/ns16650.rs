@@ -83,6 +83,11 @@ impl Ns16650 {
 impl Configuration<super::UartConfig> for Ns16650 {
     type Target = ();
     fn configure(&self, para: &super::UartConfig) -> blueos_hal::err::Result<()> {
+        // The line is left as the firmware set it up, automatic flow
+        // control isn't enabled.
+        if para.flow_ctrl != super::FlowCtrl::None {
+            return Err(blueos_hal::err::HalError::NotSupport);
+        }
         Ok(())
     }
 }
@@ -130,6 +135,10 @@ impl HasFifo for Ns16650 {
     fn is_rx_fifo_empty(&self) -> bool {
         !self.registers.lsr.is_set(LSR::DATA_READY)
     }
+
+    fn is_tx_fifo_empty(&self) -> bool {
+        self.registers.lsr.is_set(LSR::TRANS_EMPTY)
+    }
 }
 
 impl HasInterruptReg for Ns16650 {
@@ -151,7 +160,11 @@ impl HasInterruptReg for Ns16650 {
         todo!()
     }
 
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn()) {}
+    fn enabled_interrupts(&self) -> Self::InterruptType {
+        super::InterruptType::Unknown
+    }
+
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize) {}
 
     fn get_irq_nums(&self) -> &[u32] {
         &[]
@@ -0,0 +1,77 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Memory barriers for MMIO sequences.
+//!
+//! Volatile accesses to one peripheral are issued in program order, but an
+//! access to another peripheral or to normal memory may overtake them on the
+//! bus. Use a barrier where one access must have taken effect before another:
+//!
+//! - [`dsb`] after releasing a reset or enabling a clock, before the first
+//!   access to the peripheral, so that the access cannot race the enable.
+//!   [`ResetCtrl::toggle`](crate::reset::ResetCtrl::toggle) and
+//!   [`ResetCtrlWithDone::wait_done`](crate::reset::ResetCtrlWithDone::wait_done)
+//!   end with one.
+//! - [`dmb`] between filling a buffer in normal memory and starting a DMA
+//!   transfer that reads it.
+//! - [`dsb`] followed by [`isb`] after changing the system control space,
+//!   e.g. enabling the FPU in CPACR, before executing instructions that
+//!   depend on the change.
+//!
+//! On RISC-V, `dmb` and `dsb` are both a full `fence`. CSR writes take
+//! effect for the following instructions there, so `isb` only stops the
+//! compiler from reordering.
+
+/// Orders memory accesses before the barrier against those after it.
+#[inline(always)]
+pub fn dmb() {
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    unsafe {
+        core::arch::asm!("dmb sy", options(nostack, preserves_flags))
+    };
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    unsafe {
+        core::arch::asm!("fence iorw, iorw", options(nostack))
+    };
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv32",
+        target_arch = "riscv64"
+    )))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Waits until all memory accesses before the barrier have completed.
+#[inline(always)]
+pub fn dsb() {
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    unsafe {
+        core::arch::asm!("dsb sy", options(nostack, preserves_flags))
+    };
+    #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
+    dmb();
+}
+
+/// Flushes the pipeline, so that later instructions see the effect of
+/// completed system register writes.
+#[inline(always)]
+pub fn isb() {
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    unsafe {
+        core::arch::asm!("isb sy", options(nostack, preserves_flags))
+    };
+    #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
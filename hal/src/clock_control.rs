@@ -13,5 +13,19 @@
 // limitations under the License.
 
 pub trait ClockControl {
+    /// Identifies a peripheral clocked by this controller.
+    type Peripheral;
+
     fn init();
+
+    /// Waits until `peripheral` can be accessed after its clock has been
+    /// enabled and its reset released. Returns `Timeout` if it never gets
+    /// ready.
+    ///
+    /// The default only makes sure the enabling write has completed, for
+    /// controllers without a ready indication.
+    fn wait_peripheral_ready(_peripheral: Self::Peripheral) -> crate::err::Result<()> {
+        crate::barrier::dsb();
+        Ok(())
+    }
 }
@@ -20,8 +20,10 @@ pub mod err;
 use core::num::NonZeroUsize;
 
 use err::Result;
+pub mod barrier;
 pub mod clock_control;
 pub mod pinctrl;
+pub mod power;
 pub mod reset;
 pub mod uart;
 
@@ -122,12 +124,23 @@ pub trait HasInterruptReg {
     fn enable_interrupt(&self, intr: Self::InterruptType);
     fn disable_interrupt(&self, intr: Self::InterruptType);
     fn get_interrupt(&self) -> Self::InterruptType;
-    fn set_interrupt_handler(&self, handler: &'static dyn Fn());
+    /// Reads back the interrupts currently enabled in the peripheral.
+    fn enabled_interrupts(&self) -> Self::InterruptType;
+    /// Installs `handler`, which is called with `ctx` when the peripheral
+    /// raises an interrupt.
+    fn set_interrupt_handler(&self, handler: fn(usize), ctx: usize);
 
     fn clear_interrupt(&self, intr: Self::InterruptType);
     fn get_irq_nums(&self) -> &[u32];
 }
 
+/// A peripheral serving its own interrupt: it runs the handler installed
+/// with [`HasInterruptReg::set_interrupt_handler`] and acknowledges what
+/// fired, so the kernel can dispatch to it without knowing the driver.
+pub trait HandlesIrq: Send + Sync {
+    fn handle_irq(&self);
+}
+
 /// FIFO (First-In-First-Out) operations trait
 ///
 /// Provides a standard interface for FIFO buffer management in peripherals.
@@ -138,6 +151,41 @@ pub trait HasFifo {
     fn enable_fifo(&self, num: u8) -> Result<()>;
     fn is_tx_fifo_full(&self) -> bool;
     fn is_rx_fifo_empty(&self) -> bool;
+
+    /// Check if the transmit FIFO has been drained
+    ///
+    /// Reports whether bytes are still queued in the transmit FIFO. Whether
+    /// [`HasLineStatusReg::is_bus_busy`] covers the FIFO as well differs between
+    /// controllers (the PL011 BUSY bit does, a status flag tracking only the
+    /// shift register doesn't), so a complete flush waits for this first and
+    /// then for the bus to become idle.
+    fn is_tx_fifo_empty(&self) -> bool;
+
+    /// Number of bytes that can be written to the transmit FIFO without
+    /// checking again
+    ///
+    /// The default only knows whether the FIFO is full, controllers that
+    /// report the fill level override this.
+    fn tx_fifo_space(&self) -> usize {
+        if self.is_tx_fifo_full() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Number of bytes that can be read from the receive FIFO without
+    /// checking again
+    ///
+    /// The default only knows whether the FIFO is empty, controllers that
+    /// report the fill level override this.
+    fn rx_fifo_count(&self) -> usize {
+        if self.is_rx_fifo_empty() {
+            0
+        } else {
+            1
+        }
+    }
 }
 
 /// Status register operations trait
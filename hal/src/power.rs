@@ -0,0 +1,31 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Peripherals able to wake the CPU from a low-power state
+///
+/// Before entering sleep the power manager arms every configured wake source,
+/// so that the wakeup event raises an interrupt. Once awake it asks each of
+/// them whether it caused the wakeup and disarms them again.
+///
+/// Arming must only add to the interrupts the peripheral already has enabled,
+/// and disarming must only remove what arming added, so that the normal
+/// driver keeps working across a sleep.
+pub trait WakeSource: super::PlatPeri {
+    /// Enable the interrupt raised by the wakeup event
+    fn arm_wakeup(&self);
+    /// Undo [`WakeSource::arm_wakeup`]
+    fn disarm_wakeup(&self);
+    /// Check if the wakeup event has occurred since the source was armed
+    fn is_wake_cause(&self) -> bool;
+}
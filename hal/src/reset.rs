@@ -20,6 +20,9 @@ pub trait ResetCtrl: super::PlatPeri {
     fn toggle(&self, id: u32) {
         self.set_reset(id);
         self.clear_reset(id);
+        // The reset controller is another peripheral, make sure the release
+        // has landed before the caller touches the device.
+        crate::barrier::dsb();
     }
 }
 pub trait HasDoneReg {
@@ -29,5 +32,22 @@ pub trait HasDoneReg {
 pub trait ResetCtrlWithDone: ResetCtrl + HasDoneReg {
     fn wait_done(&self, id: u32) {
         while !self.is_done(id) {}
+        crate::barrier::dsb();
+    }
+
+    /// Holds `id` in reset for at least `min_us`, then releases it and waits
+    /// for the reset to complete.
+    ///
+    /// There is no timer at this level, so the pulse is timed by spinning
+    /// for `min_us` worth of cycles at `cpu_hz`. Every iteration takes at
+    /// least one cycle, so the pulse is never shorter than requested.
+    fn reset_pulse(&self, id: u32, min_us: u32, cpu_hz: u32) {
+        self.set_reset(id);
+        crate::barrier::dsb();
+        for _ in 0..min_us as u64 * cpu_hz as u64 / 1_000_000 {
+            core::hint::spin_loop();
+        }
+        self.clear_reset(id);
+        self.wait_done(id);
     }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::{
+    err::{HalError, Result},
     uart, Has8bitDataReg, HasFifo, HasInterruptReg, HasLineStatusReg, HasRestReg, HasStatusReg,
 };
 
@@ -24,6 +25,11 @@ pub trait Uart<P, T, I, S>:
     + Has8bitDataReg
     + HasLineStatusReg
 {
+    /// Reads the modem lines, latched receive errors and FIFO flags.
+    /// UARTs that can't report them return `NotSupport`.
+    fn get_ctrl_status(&self) -> Result<S> {
+        Err(HalError::NotSupport)
+    }
 }
 
 pub trait UartWithReset<P, T, I, S>:
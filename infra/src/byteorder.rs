@@ -0,0 +1,104 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading multi-byte values out of register and packet buffers.
+//!
+//! Each function reads the first bytes of `buf` in the named byte order and
+//! returns `None` if `buf` is too short, so a wrong slice bound shows up as
+//! an error rather than a panic or a silently misassembled value. The 24-bit
+//! readers return the value widened to 32 bits, sign-extended for `i24`.
+
+macro_rules! readers {
+    ($($be:ident, $le:ident: $ty:ty;)*) => {
+        $(
+            #[inline]
+            pub fn $be(buf: &[u8]) -> Option<$ty> {
+                Some(<$ty>::from_be_bytes(bytes(buf)?))
+            }
+
+            #[inline]
+            pub fn $le(buf: &[u8]) -> Option<$ty> {
+                Some(<$ty>::from_le_bytes(bytes(buf)?))
+            }
+        )*
+    };
+}
+
+#[inline]
+fn bytes<const N: usize>(buf: &[u8]) -> Option<[u8; N]> {
+    buf.get(..N)?.try_into().ok()
+}
+
+readers! {
+    u16_from_be, u16_from_le: u16;
+    i16_from_be, i16_from_le: i16;
+    u32_from_be, u32_from_le: u32;
+    i32_from_be, i32_from_le: i32;
+}
+
+#[inline]
+pub fn u24_from_be(buf: &[u8]) -> Option<u32> {
+    let [b0, b1, b2] = bytes(buf)?;
+    Some(u32::from_be_bytes([0, b0, b1, b2]))
+}
+
+#[inline]
+pub fn u24_from_le(buf: &[u8]) -> Option<u32> {
+    let [b0, b1, b2] = bytes(buf)?;
+    Some(u32::from_le_bytes([b0, b1, b2, 0]))
+}
+
+#[inline]
+pub fn i24_from_be(buf: &[u8]) -> Option<i32> {
+    u24_from_be(buf).map(sign_extend_24)
+}
+
+#[inline]
+pub fn i24_from_le(buf: &[u8]) -> Option<i32> {
+    u24_from_le(buf).map(sign_extend_24)
+}
+
+#[inline]
+fn sign_extend_24(v: u32) -> i32 {
+    // Move bit 23 into the sign bit, then shift back arithmetically.
+    ((v << 8) as i32) >> 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders() {
+        let buf = [0x80, 0x01, 0x02, 0x03];
+        assert_eq!(u16_from_be(&buf), Some(0x8001));
+        assert_eq!(u16_from_le(&buf), Some(0x0180));
+        assert_eq!(i16_from_be(&buf), Some(-0x7FFF));
+        assert_eq!(u24_from_be(&buf), Some(0x80_0102));
+        assert_eq!(u24_from_le(&buf), Some(0x02_0180));
+        assert_eq!(i24_from_be(&buf), Some(0x80_0102 - 0x100_0000));
+        assert_eq!(i24_from_le(&buf), Some(0x02_0180));
+        assert_eq!(u32_from_be(&buf), Some(0x8001_0203));
+        assert_eq!(i32_from_le(&buf), Some(0x0302_0180));
+    }
+
+    #[test]
+    fn too_short() {
+        let buf = [0x12, 0x34, 0x56];
+        assert_eq!(u16_from_be(&buf[2..]), None);
+        assert_eq!(u24_from_le(&buf), Some(0x56_3412));
+        assert_eq!(i32_from_be(&buf), None);
+        assert_eq!(u16_from_le(&[]), None);
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consistent Overhead Byte Stuffing.
+//!
+//! COBS rewrites a packet so that it contains no zero bytes, which leaves
+//! `0x00` free to be used as a frame delimiter on a raw byte stream such as a
+//! serial link. A receiver that loses sync simply discards bytes until the
+//! next delimiter. Both directions work on caller provided buffers and never
+//! allocate.
+//!
+//! [`encode`] does not emit the trailing delimiter, the sender is expected to
+//! write [`DELIMITER`] after each frame. [`decode`] accepts a frame with or
+//! without the trailing delimiter.
+
+/// Byte used to separate encoded frames on the wire.
+pub const DELIMITER: u8 = 0;
+
+// Longest run of non-zero bytes a single code byte can describe.
+const MAX_BLOCK: u8 = 0xFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The frame ended in the middle of a block, or was empty.
+    Truncated,
+    /// The destination buffer cannot hold the decoded data.
+    BufferTooSmall,
+}
+
+pub type Result<T> = core::result::Result<T, DecodeError>;
+
+/// Upper bound of the encoded size of `len` bytes, excluding the delimiter.
+///
+/// COBS adds one byte per started run of 254 bytes, so the overhead is at
+/// most `len / 254 + 1`.
+pub const fn max_encoded_len(len: usize) -> usize {
+    len + len / (MAX_BLOCK as usize - 1) + 1
+}
+
+/// Encodes `src` into `dst` and returns the number of bytes written.
+///
+/// The output contains no zero bytes and is not followed by a delimiter.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than [`max_encoded_len`] of `src.len()`.
+pub fn encode(src: &[u8], dst: &mut [u8]) -> usize {
+    assert!(
+        dst.len() >= max_encoded_len(src.len()),
+        "COBS output buffer too small"
+    );
+    let mut code_idx = 0;
+    let mut out = 1;
+    let mut code: u8 = 1;
+    for (i, &byte) in src.iter().enumerate() {
+        if byte == 0 {
+            dst[code_idx] = code;
+            code_idx = out;
+            out += 1;
+            code = 1;
+            continue;
+        }
+        dst[out] = byte;
+        out += 1;
+        code += 1;
+        // A full block is closed without an implied zero. Only start a new
+        // one if there is more input, otherwise a spurious 0x01 would follow.
+        if code == MAX_BLOCK && i + 1 < src.len() {
+            dst[code_idx] = code;
+            code_idx = out;
+            out += 1;
+            code = 1;
+        }
+    }
+    dst[code_idx] = code;
+    out
+}
+
+/// Decodes one frame from `src` into `dst` and returns the decoded length.
+///
+/// Decoding stops at the first [`DELIMITER`] or at the end of `src`.
+pub fn decode(src: &[u8], dst: &mut [u8]) -> Result<usize> {
+    let frame = match src.iter().position(|&b| b == DELIMITER) {
+        Some(end) => &src[..end],
+        None => src,
+    };
+    if frame.is_empty() {
+        return Err(DecodeError::Truncated);
+    }
+
+    let mut i = 0;
+    let mut out = 0;
+    while i < frame.len() {
+        let code = frame[i];
+        let run = code as usize - 1;
+        i += 1;
+        if i + run > frame.len() {
+            return Err(DecodeError::Truncated);
+        }
+        if out + run > dst.len() {
+            return Err(DecodeError::BufferTooSmall);
+        }
+        dst[out..out + run].copy_from_slice(&frame[i..i + run]);
+        out += run;
+        i += run;
+        // Every block but a full one implies a zero, except the last block.
+        if code != MAX_BLOCK && i < frame.len() {
+            if out == dst.len() {
+                return Err(DecodeError::BufferTooSmall);
+            }
+            dst[out] = 0;
+            out += 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(raw: &[u8], encoded: &[u8]) {
+        let mut enc = vec![0u8; max_encoded_len(raw.len())];
+        let n = encode(raw, &mut enc);
+        assert_eq!(&enc[..n], encoded);
+        assert!(!enc[..n].contains(&DELIMITER));
+
+        let mut dec = vec![0u8; raw.len()];
+        assert_eq!(decode(encoded, &mut dec), Ok(raw.len()));
+        assert_eq!(&dec[..], raw);
+    }
+
+    #[test]
+    fn test_vectors() {
+        check(&[], &[0x01]);
+        check(&[0x00], &[0x01, 0x01]);
+        check(&[0x00, 0x00], &[0x01, 0x01, 0x01]);
+        check(&[0x00, 0x11, 0x00], &[0x01, 0x02, 0x11, 0x01]);
+        check(&[0x11, 0x22, 0x00, 0x33], &[0x03, 0x11, 0x22, 0x02, 0x33]);
+        check(&[0x11, 0x22, 0x33, 0x44], &[0x05, 0x11, 0x22, 0x33, 0x44]);
+        check(&[0x11, 0x00, 0x00, 0x00], &[0x02, 0x11, 0x01, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn test_long_runs() {
+        // 01..FE fills exactly one block.
+        let raw: Vec<u8> = (0x01..=0xFE).collect();
+        let mut encoded = vec![0xFF];
+        encoded.extend_from_slice(&raw);
+        check(&raw, &encoded);
+
+        // 00 01..FE
+        let mut raw2 = vec![0x00];
+        raw2.extend(0x01..=0xFE);
+        let mut encoded = vec![0x01, 0xFF];
+        encoded.extend(0x01..=0xFE);
+        check(&raw2, &encoded);
+
+        // 01..FF spills one byte into a second block.
+        let raw: Vec<u8> = (0x01..=0xFF).collect();
+        let mut encoded = vec![0xFF];
+        encoded.extend(0x01..=0xFE);
+        encoded.extend_from_slice(&[0x02, 0xFF]);
+        check(&raw, &encoded);
+
+        // 02..FF 00
+        let mut raw: Vec<u8> = (0x02..=0xFF).collect();
+        raw.push(0x00);
+        let mut encoded = vec![0xFF];
+        encoded.extend(0x02..=0xFF);
+        encoded.extend_from_slice(&[0x01, 0x01]);
+        check(&raw, &encoded);
+
+        // 03..FF 00 01
+        let mut raw: Vec<u8> = (0x03..=0xFF).collect();
+        raw.extend_from_slice(&[0x00, 0x01]);
+        let mut encoded = vec![0xFE];
+        encoded.extend(0x03..=0xFF);
+        encoded.extend_from_slice(&[0x02, 0x01]);
+        check(&raw, &encoded);
+    }
+
+    #[test]
+    fn test_max_overhead() {
+        for len in [0, 1, 253, 254, 255, 508, 509, 1024] {
+            let raw = vec![0xAAu8; len];
+            let mut enc = vec![0u8; max_encoded_len(len)];
+            let n = encode(&raw, &mut enc);
+            assert!(n <= max_encoded_len(len));
+            assert_eq!(n, len + len.div_ceil(254).max(1));
+            let mut dec = vec![0u8; len];
+            assert_eq!(decode(&enc[..n], &mut dec), Ok(len));
+            assert_eq!(dec, raw);
+        }
+    }
+
+    #[test]
+    fn test_decode_delimiter() {
+        let mut dec = [0u8; 8];
+        let wire = [0x03, 0x11, 0x22, 0x02, 0x33, DELIMITER, 0x05];
+        assert_eq!(decode(&wire, &mut dec), Ok(4));
+        assert_eq!(&dec[..4], &[0x11, 0x22, 0x00, 0x33]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut dec = [0u8; 8];
+        assert_eq!(decode(&[], &mut dec), Err(DecodeError::Truncated));
+        assert_eq!(decode(&[DELIMITER], &mut dec), Err(DecodeError::Truncated));
+        assert_eq!(
+            decode(&[0x05, 0x11, 0x22], &mut dec),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            decode(&[0x03, 0x11, 0x00, 0x22], &mut dec),
+            Err(DecodeError::Truncated)
+        );
+
+        let mut small = [0u8; 2];
+        assert_eq!(
+            decode(&[0x05, 0x11, 0x22, 0x33, 0x44], &mut small),
+            Err(DecodeError::BufferTooSmall)
+        );
+        assert_eq!(
+            decode(&[0x03, 0x11, 0x22, 0x02, 0x33], &mut small),
+            Err(DecodeError::BufferTooSmall)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Table-driven CRC-8 and CRC-16.
+//!
+//! The lookup tables are built at compile time, so each algorithm below is a
+//! `static` in flash. Polynomials are given in normal (MSB-first) form, and a
+//! reflected algorithm processes the bits of each byte LSB first, as the
+//! 1-Wire bus sends them. None of the provided algorithms use a final XOR.
+//!
+//! A CRC over several buffers is computed by starting from
+//! [`Crc8::init`]/[`Crc16::init`] and passing the result of each
+//! [`update`](Crc16::update) to the next.
+
+pub struct Crc8 {
+    table: [u8; 256],
+    init: u8,
+}
+
+impl Crc8 {
+    pub const fn new(poly: u8, init: u8, reflected: bool) -> Self {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u8;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if reflected {
+                    if crc & 1 != 0 {
+                        (crc >> 1) ^ poly.reverse_bits()
+                    } else {
+                        crc >> 1
+                    }
+                } else if crc & 0x80 != 0 {
+                    (crc << 1) ^ poly
+                } else {
+                    crc << 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        let init = if reflected { init.reverse_bits() } else { init };
+        Self { table, init }
+    }
+
+    pub const fn init(&self) -> u8 {
+        self.init
+    }
+
+    pub const fn update(&self, mut crc: u8, data: &[u8]) -> u8 {
+        let mut i = 0;
+        while i < data.len() {
+            crc = self.table[(crc ^ data[i]) as usize];
+            i += 1;
+        }
+        crc
+    }
+
+    pub const fn checksum(&self, data: &[u8]) -> u8 {
+        self.update(self.init, data)
+    }
+}
+
+pub struct Crc16 {
+    table: [u16; 256],
+    init: u16,
+    reflected: bool,
+}
+
+impl Crc16 {
+    pub const fn new(poly: u16, init: u16, reflected: bool) -> Self {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = if reflected { i as u16 } else { (i as u16) << 8 };
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if reflected {
+                    if crc & 1 != 0 {
+                        (crc >> 1) ^ poly.reverse_bits()
+                    } else {
+                        crc >> 1
+                    }
+                } else if crc & 0x8000 != 0 {
+                    (crc << 1) ^ poly
+                } else {
+                    crc << 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        let init = if reflected { init.reverse_bits() } else { init };
+        Self {
+            table,
+            init,
+            reflected,
+        }
+    }
+
+    pub const fn init(&self) -> u16 {
+        self.init
+    }
+
+    pub const fn update(&self, mut crc: u16, data: &[u8]) -> u16 {
+        let mut i = 0;
+        while i < data.len() {
+            crc = if self.reflected {
+                (crc >> 8) ^ self.table[((crc as u8) ^ data[i]) as usize]
+            } else {
+                (crc << 8) ^ self.table[(((crc >> 8) as u8) ^ data[i]) as usize]
+            };
+            i += 1;
+        }
+        crc
+    }
+
+    pub const fn checksum(&self, data: &[u8]) -> u16 {
+        self.update(self.init, data)
+    }
+}
+
+/// CRC-8/SMBUS, the SMBus packet error code.
+pub static CRC8_SMBUS: Crc8 = Crc8::new(0x07, 0x00, false);
+/// CRC-8/NRSC-5, used by the Sensirion SHT and SCD sensors.
+pub static CRC8_SENSIRION: Crc8 = Crc8::new(0x31, 0xFF, false);
+/// CRC-8/MAXIM-DOW, used by Dallas 1-Wire ROM codes and scratchpads.
+pub static CRC8_MAXIM: Crc8 = Crc8::new(0x31, 0x00, true);
+/// CRC-16/IBM-3740, commonly called CRC-16/CCITT-FALSE.
+pub static CRC16_CCITT_FALSE: Crc16 = Crc16::new(0x1021, 0xFFFF, false);
+/// CRC-16/XMODEM.
+pub static CRC16_XMODEM: Crc16 = Crc16::new(0x1021, 0x0000, false);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn check_values() {
+        assert_eq!(CRC8_SMBUS.checksum(CHECK), 0xF4);
+        assert_eq!(CRC8_SENSIRION.checksum(CHECK), 0xF7);
+        assert_eq!(CRC8_MAXIM.checksum(CHECK), 0xA1);
+        assert_eq!(CRC16_CCITT_FALSE.checksum(CHECK), 0x29B1);
+        assert_eq!(CRC16_XMODEM.checksum(CHECK), 0x31C3);
+        // Reflected CRC-16/KERMIT.
+        assert_eq!(Crc16::new(0x1021, 0x0000, true).checksum(CHECK), 0x2189);
+    }
+
+    #[test]
+    fn incremental() {
+        let (head, tail) = CHECK.split_at(4);
+        let crc = CRC16_CCITT_FALSE.update(CRC16_CCITT_FALSE.init(), head);
+        assert_eq!(CRC16_CCITT_FALSE.update(crc, tail), 0x29B1);
+        let crc = CRC8_MAXIM.update(CRC8_MAXIM.init(), head);
+        assert_eq!(CRC8_MAXIM.update(crc, tail), 0xA1);
+    }
+
+    #[test]
+    fn sensor_vectors() {
+        // Example from the SHT3x datasheet.
+        assert_eq!(CRC8_SENSIRION.checksum(&[0xBE, 0xEF]), 0x92);
+        // A 1-Wire ROM code ends with the CRC of the first seven bytes, so
+        // the CRC over all eight is zero.
+        let rom = [0x28, 0xFF, 0x4B, 0x6C, 0x31, 0x17, 0x03];
+        let crc = CRC8_MAXIM.checksum(&rom);
+        assert_eq!(CRC8_MAXIM.update(crc, &[crc]), 0);
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Buffers that a DMA channel can be pointed at.
+//!
+//! This module does not start transfers. A driver that does should take a
+//! `&'static mut DmaBuf<N>` rather than an arbitrary slice, so that a
+//! transfer can't outlive a stack buffer. The buffer is aligned and sized to
+//! whole cache lines so that cache maintenance on it never touches a
+//! neighbouring object.
+//!
+//! On AArch64 the sync calls clean or invalidate the data cache by address.
+//! The Cortex-M and RISC-V targets have no data cache in front of DMA, so a
+//! barrier is all they need there.
+
+use core::ops::{Deref, DerefMut};
+
+/// Alignment and size granule of a [`DmaBuf`], the largest cache line of the
+/// supported cores (64 bytes on the Cortex-A cores).
+pub const DMA_ALIGN: usize = 64;
+
+#[repr(C, align(64))]
+pub struct DmaBuf<const N: usize> {
+    data: [u8; N],
+}
+
+impl<const N: usize> DmaBuf<N> {
+    const VALID: () = assert!(
+        N != 0 && N % DMA_ALIGN == 0,
+        "DmaBuf size must be a non-zero multiple of DMA_ALIGN"
+    );
+
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self { data: [0; N] }
+    }
+
+    /// Address to program into the DMA channel. Physical and virtual
+    /// addresses are the same on the targets using this.
+    pub fn addr(&self) -> usize {
+        self.data.as_ptr() as usize
+    }
+
+    /// Makes CPU writes visible to the device. Call before starting a
+    /// memory-to-peripheral transfer.
+    ///
+    /// Cleans the buffer out of the data cache where there is one, then
+    /// orders the writes before the access that starts the DMA.
+    pub fn sync_for_device(&self) {
+        #[cfg(target_arch = "aarch64")]
+        self.for_each_cache_line(|line| unsafe {
+            core::arch::asm!("dc cvac, {}", in(reg) line, options(nostack, preserves_flags))
+        });
+        barrier();
+    }
+
+    /// Makes device writes visible to the CPU. Call after a
+    /// peripheral-to-memory transfer has completed.
+    ///
+    /// Invalidates the buffer in the data cache where there is one, so that
+    /// later reads don't hit stale lines.
+    pub fn sync_for_cpu(&self) {
+        barrier();
+        #[cfg(target_arch = "aarch64")]
+        {
+            self.for_each_cache_line(|line| unsafe {
+                core::arch::asm!("dc ivac, {}", in(reg) line, options(nostack, preserves_flags))
+            });
+            barrier();
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn for_each_cache_line(&self, mut op: impl FnMut(usize)) {
+        let ctr: usize;
+        unsafe {
+            core::arch::asm!("mrs {}, ctr_el0", out(reg) ctr, options(nomem, nostack, preserves_flags))
+        };
+        // DminLine, log2 of the smallest data cache line in words.
+        let line = 4 << ((ctr >> 16) & 0xf);
+        let start = self.addr() & !(line - 1);
+        for addr in (start..self.addr() + N).step_by(line) {
+            op(addr);
+        }
+    }
+}
+
+// The same barrier as `blueos_hal::barrier::dmb`, or `dsb` where cache
+// maintenance has to complete before the device is told to go.
+#[inline(always)]
+fn barrier() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dmb sy", options(nostack, preserves_flags))
+    };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        core::arch::asm!("dsb sy", options(nostack, preserves_flags))
+    };
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    unsafe {
+        core::arch::asm!("fence iorw, iorw", options(nostack))
+    };
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv32",
+        target_arch = "riscv64"
+    )))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+impl<const N: usize> Default for DmaBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for DmaBuf<N> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<const N: usize> DerefMut for DmaBuf<N> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned() {
+        static mut BUF: DmaBuf<128> = DmaBuf::new();
+        let buf = unsafe { &mut *core::ptr::addr_of_mut!(BUF) };
+        assert_eq!(buf.addr() % DMA_ALIGN, 0);
+        assert_eq!(buf.len(), 128);
+        assert_eq!(core::mem::size_of::<DmaBuf<128>>(), 128);
+
+        buf[..3].copy_from_slice(b"abc");
+        buf.sync_for_device();
+        buf.sync_for_cpu();
+        assert_eq!(&buf[..4], b"abc\0");
+    }
+}
@@ -30,10 +30,17 @@
 #![feature(slice_ptr_get)]
 #![feature(strict_provenance_atomic_ptr)]
 
+pub mod byteorder;
+pub mod cobs;
+pub mod crc;
+pub mod dmabuf;
 pub mod intrusive;
 pub mod list;
+pub mod pubsub;
 pub mod ringbuffer;
+pub mod slip;
 pub mod spinarc;
 pub mod string;
+pub mod ticketlock;
 pub mod tinyarc;
 pub mod tinyrwlock;
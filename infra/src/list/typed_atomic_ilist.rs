@@ -168,6 +168,49 @@ impl<T, A: Adapter<T>> AtomicListHead<T, A> {
     }
 }
 
+/// Inconsistency found by [`AtomicListHead::check_chain`].
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListCorruption {
+    /// A node's `next` points to itself.
+    SelfLoop { index: usize },
+    /// A node's `next.prev` doesn't point back to it. Cycles are reported
+    /// this way as well, since the node closing the cycle can't be the
+    /// `prev` of the node it points to.
+    BrokenLink { index: usize },
+    /// The walk fell off the list before reaching the tail.
+    Unterminated { index: usize },
+}
+
+#[cfg(debug_assertions)]
+impl<T, A: Adapter<T>> AtomicListHead<T, A> {
+    /// Walks from `head` to `tail` and checks every link, returning the
+    /// number of nodes in between. `index` in the error is the position of
+    /// the offending node, `head` being 0. The list lock must be held.
+    pub fn check_chain(head: &Self, tail: &Self) -> Result<usize, ListCorruption> {
+        let tail = tail as *const Self as *mut Self;
+        let mut cur = head as *const Self as *mut Self;
+        let mut index = 0;
+        loop {
+            let next = unsafe { (*cur).next_ptr() };
+            if next.is_null() {
+                return Err(ListCorruption::Unterminated { index });
+            }
+            if next == cur {
+                return Err(ListCorruption::SelfLoop { index });
+            }
+            if unsafe { (*next).prev_ptr() } != cur {
+                return Err(ListCorruption::BrokenLink { index });
+            }
+            if next == tail {
+                return Ok(index);
+            }
+            cur = next;
+            index += 1;
+        }
+    }
+}
+
 unsafe impl<T, A: crate::intrusive::Adapter<T>> Sync for AtomicListHead<T, A> {}
 impl<T, A> !Send for AtomicListHead<T, A> {}
 
@@ -295,6 +338,43 @@ mod tests {
         assert!(!c.lh.is_detached());
     }
 
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_check_chain() {
+        type Ty = AtomicListHead<Foo, OffsetOfLh>;
+        let mut head = Foo::default();
+        let mut tail = Foo::default();
+        let mut a = Foo::default();
+        let mut b = Foo::default();
+        assert!(Ty::insert_after(&mut head.lh, &mut tail.lh));
+        assert_eq!(Ty::check_chain(&head.lh, &tail.lh), Ok(0));
+        assert!(Ty::insert_before(&mut tail.lh, &mut a.lh));
+        assert!(Ty::insert_before(&mut tail.lh, &mut b.lh));
+        assert_eq!(Ty::check_chain(&head.lh, &tail.lh), Ok(2));
+
+        let saved = b.lh.prev.load(Ordering::Relaxed);
+        b.lh.prev.store(&mut head.lh as *mut _, Ordering::Relaxed);
+        assert_eq!(
+            Ty::check_chain(&head.lh, &tail.lh),
+            Err(ListCorruption::BrokenLink { index: 1 })
+        );
+        b.lh.prev.store(saved, Ordering::Relaxed);
+
+        let saved = a.lh.next;
+        a.lh.next = Some(NonNull::from_mut(&mut a.lh));
+        assert_eq!(
+            Ty::check_chain(&head.lh, &tail.lh),
+            Err(ListCorruption::SelfLoop { index: 1 })
+        );
+        a.lh.next = None;
+        assert_eq!(
+            Ty::check_chain(&head.lh, &tail.lh),
+            Err(ListCorruption::Unterminated { index: 1 })
+        );
+        a.lh.next = saved;
+        assert_eq!(Ty::check_chain(&head.lh, &tail.lh), Ok(2));
+    }
+
     #[test]
     fn test_concurrent_ops() {
         type Ty = AtomicListHead<Foo, OffsetOfLh>;
@@ -0,0 +1,301 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Broadcast of values from one producer to any number of subscribers.
+//!
+//! A [`Topic`] keeps the last `N` published values in a ring. Publishing
+//! never waits, it overwrites the oldest value. Every [`Subscriber`] has its
+//! own read position, so subscribers don't take values from each other. One
+//! that falls more than `N` values behind skips to the oldest value still
+//! kept and counts the ones it lost in [`Subscriber::missed`].
+//!
+//! Each slot carries a sequence number that is odd while the slot is being
+//! written, so readers detect a value that changed under them. Such a value
+//! is skipped and counted as missed rather than waited for.
+//! Neither side takes a lock, which makes publishing safe from interrupt
+//! handlers. There is at most one [`Publisher`] at a time.
+
+use core::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering},
+};
+
+struct Slot<T> {
+    // 2 * index + 1 while the value of `index` is written, 2 * index + 2
+    // once it is complete.
+    seq: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+fn complete(index: usize) -> usize {
+    index.wrapping_mul(2).wrapping_add(2)
+}
+
+pub struct Topic<T: Copy, const N: usize> {
+    slots: [Slot<T>; N],
+    // Number of values published so far, the index of the next one.
+    head: AtomicUsize,
+    has_publisher: AtomicBool,
+}
+
+// SAFETY: Values are copied in and out, and a reader only keeps a value
+// whose slot sequence did not change while it was copied.
+unsafe impl<T: Copy + Send, const N: usize> Sync for Topic<T, N> {}
+
+impl<T: Copy, const N: usize> Topic<T, N> {
+    const VALID: () = assert!(N != 0, "Topic needs at least one slot");
+
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self {
+            slots: [const { Slot::new() }; N],
+            head: AtomicUsize::new(0),
+            has_publisher: AtomicBool::new(false),
+        }
+    }
+
+    /// Takes the publishing side, `None` if it is already taken. It is
+    /// released when the [`Publisher`] is dropped.
+    pub fn publisher(&self) -> Option<Publisher<'_, T, N>> {
+        self.has_publisher
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| Publisher { topic: self })
+    }
+
+    /// Subscribes to the values published from now on.
+    pub fn subscribe(&self) -> Subscriber<'_, T, N> {
+        Subscriber {
+            topic: self,
+            next: self.head.load(Ordering::Acquire),
+            missed: 0,
+        }
+    }
+
+    /// Subscribes starting with the oldest value still kept, so the first
+    /// `recv`s return the history.
+    pub fn subscribe_with_history(&self) -> Subscriber<'_, T, N> {
+        let head = self.head.load(Ordering::Acquire);
+        Subscriber {
+            topic: self,
+            next: head.saturating_sub(N),
+            missed: 0,
+        }
+    }
+
+    /// Number of values published so far.
+    pub fn published(&self) -> usize {
+        self.head.load(Ordering::Acquire)
+    }
+
+    /// The most recently published value, `None` if there is none or it
+    /// is being overwritten.
+    pub fn latest(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Acquire);
+        if head == 0 {
+            return None;
+        }
+        // Retrying could spin forever on a publisher preempted mid-write.
+        self.read(head.wrapping_sub(1))
+    }
+
+    // Copies the value of `index` out, `None` if its slot has been reused
+    // or is being written.
+    fn read(&self, index: usize) -> Option<T> {
+        let slot = &self.slots[index % N];
+        let seq = slot.seq.load(Ordering::Acquire);
+        if seq != complete(index) {
+            return None;
+        }
+        // SAFETY: A complete sequence means the slot was initialized. The
+        // copy may be torn by a concurrent write, which the second check
+        // below catches before the copy is used.
+        let value = unsafe { ptr::read_volatile(slot.value.get()) };
+        fence(Ordering::Acquire);
+        if slot.seq.load(Ordering::Relaxed) != seq {
+            return None;
+        }
+        Some(unsafe { value.assume_init() })
+    }
+}
+
+impl<T: Copy, const N: usize> Default for Topic<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Publisher<'a, T: Copy, const N: usize> {
+    topic: &'a Topic<T, N>,
+}
+
+impl<T: Copy, const N: usize> Publisher<'_, T, N> {
+    pub fn publish(&mut self, value: T) {
+        let topic = self.topic;
+        let index = topic.head.load(Ordering::Relaxed);
+        let slot = &topic.slots[index % N];
+        slot.seq
+            .store(index.wrapping_mul(2).wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: Only the publisher writes slots. Readers validate what
+        // they copied against the sequence number.
+        unsafe { ptr::write_volatile(slot.value.get(), MaybeUninit::new(value)) };
+        slot.seq.store(complete(index), Ordering::Release);
+        topic.head.store(index.wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl<T: Copy, const N: usize> Drop for Publisher<'_, T, N> {
+    fn drop(&mut self) {
+        self.topic.has_publisher.store(false, Ordering::Release);
+    }
+}
+
+pub struct Subscriber<'a, T: Copy, const N: usize> {
+    topic: &'a Topic<T, N>,
+    next: usize,
+    missed: usize,
+}
+
+impl<T: Copy, const N: usize> Subscriber<'_, T, N> {
+    /// Returns the next value not yet seen by this subscriber, `None` if
+    /// there is none.
+    pub fn recv(&mut self) -> Option<T> {
+        loop {
+            let head = self.topic.head.load(Ordering::Acquire);
+            if self.next == head {
+                return None;
+            }
+            let behind = head.wrapping_sub(self.next);
+            if behind > N {
+                self.missed += behind - N;
+                self.next = head.wrapping_sub(N);
+            }
+            // A failed read means the value is overwritten or being
+            // overwritten. Skip it rather than wait for a publisher that may
+            // be preempted mid-write.
+            let value = self.topic.read(self.next);
+            self.next = self.next.wrapping_add(1);
+            match value {
+                Some(value) => return Some(value),
+                None => self.missed += 1,
+            }
+        }
+    }
+
+    /// Values that were overwritten before this subscriber read them.
+    pub fn missed(&self) -> usize {
+        self.missed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_subscribers() {
+        let topic = Topic::<u32, 4>::new();
+        let mut publisher = topic.publisher().unwrap();
+        assert!(topic.publisher().is_none());
+        assert_eq!(topic.latest(), None);
+
+        let mut a = topic.subscribe();
+        let mut b = topic.subscribe();
+        publisher.publish(1);
+        publisher.publish(2);
+        assert_eq!(a.recv(), Some(1));
+        assert_eq!(a.recv(), Some(2));
+        assert_eq!(a.recv(), None);
+        publisher.publish(3);
+        // b has not read anything yet and still sees every value.
+        assert_eq!(b.recv(), Some(1));
+        assert_eq!(b.recv(), Some(2));
+        assert_eq!(b.recv(), Some(3));
+        assert_eq!(a.recv(), Some(3));
+        assert_eq!(topic.latest(), Some(3));
+        assert_eq!((a.missed(), b.missed()), (0, 0));
+    }
+
+    #[test]
+    fn lagging_subscriber() {
+        let topic = Topic::<u32, 4>::new();
+        let mut publisher = topic.publisher().unwrap();
+        let mut sub = topic.subscribe();
+        for i in 0..10 {
+            publisher.publish(i);
+        }
+        assert_eq!(sub.recv(), Some(6));
+        assert_eq!(sub.missed(), 6);
+
+        let mut late = topic.subscribe_with_history();
+        assert_eq!(late.recv(), Some(6));
+        drop(publisher);
+        assert!(topic.publisher().is_some());
+    }
+
+    #[test]
+    fn publisher_preempted_mid_write() {
+        let topic = Topic::<u32, 4>::new();
+        let mut publisher = topic.publisher().unwrap();
+        let mut sub = topic.subscribe();
+        for i in 0..4 {
+            publisher.publish(i);
+        }
+        // The next publish has marked the slot of value 0 odd and stopped.
+        topic.slots[0].seq.store(2 * 4 + 1, Ordering::Relaxed);
+        assert_eq!(sub.recv(), Some(1));
+        assert_eq!(sub.missed(), 1);
+
+        let single = Topic::<u32, 1>::new();
+        let mut publisher = single.publisher().unwrap();
+        publisher.publish(7);
+        single.slots[0].seq.store(2 + 1, Ordering::Relaxed);
+        assert_eq!(single.latest(), None);
+    }
+
+    #[test]
+    fn concurrent() {
+        extern crate std;
+        static TOPIC: Topic<(u32, u32), 8> = Topic::new();
+        let writer = std::thread::spawn(|| {
+            let mut publisher = TOPIC.publisher().unwrap();
+            for i in 0..100_000 {
+                publisher.publish((i, !i));
+            }
+        });
+        let mut sub = TOPIC.subscribe();
+        let mut last = None;
+        while !writer.is_finished() || sub.recv().is_some() {
+            if let Some((a, b)) = sub.recv() {
+                // Never a torn value, and always in order.
+                assert_eq!(b, !a);
+                assert!(last.is_none_or(|l| a > l));
+                last = Some(a);
+            }
+        }
+        writer.join().unwrap();
+    }
+}
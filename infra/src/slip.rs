@@ -0,0 +1,236 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serial Line Internet Protocol framing, as described in RFC 1055.
+//!
+//! Each packet is wrapped in [`END`] bytes. An `END` or [`ESC`] inside the
+//! payload is replaced by `ESC ESC_END` or `ESC ESC_ESC`. Unlike COBS the
+//! encoding can be produced byte by byte, which makes it convenient to write
+//! straight into a UART without an intermediate buffer.
+
+pub const END: u8 = 0xC0;
+pub const ESC: u8 = 0xDB;
+pub const ESC_END: u8 = 0xDC;
+pub const ESC_ESC: u8 = 0xDD;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// `ESC` was followed by something other than `ESC_END` or `ESC_ESC`.
+    BadEscape,
+    /// The packet does not fit in the destination buffer.
+    Overflow,
+}
+
+pub type Result<T> = core::result::Result<T, DecodeError>;
+
+/// Upper bound of the encoded size of `len` bytes, including both `END`s.
+pub const fn max_encoded_len(len: usize) -> usize {
+    2 * len + 2
+}
+
+/// Returns the byte that follows `ESC` when `byte` must be escaped.
+#[inline]
+pub const fn escape(byte: u8) -> Option<u8> {
+    match byte {
+        END => Some(ESC_END),
+        ESC => Some(ESC_ESC),
+        _ => None,
+    }
+}
+
+/// Encodes `src` into `dst`, framed by a leading and a trailing `END`, and
+/// returns the number of bytes written.
+///
+/// The leading `END` flushes any line noise the receiver may have collected.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than [`max_encoded_len`] of `src.len()`.
+pub fn encode(src: &[u8], dst: &mut [u8]) -> usize {
+    assert!(
+        dst.len() >= max_encoded_len(src.len()),
+        "SLIP output buffer too small"
+    );
+    let mut out = 0;
+    dst[out] = END;
+    out += 1;
+    for &byte in src {
+        if let Some(escaped) = escape(byte) {
+            dst[out] = ESC;
+            dst[out + 1] = escaped;
+            out += 2;
+        } else {
+            dst[out] = byte;
+            out += 1;
+        }
+    }
+    dst[out] = END;
+    out + 1
+}
+
+/// Incremental SLIP decoder.
+///
+/// Bytes are fed one at a time as they come off the wire. Empty packets,
+/// produced by back-to-back `END`s, are skipped. After an error the rest of
+/// the packet is discarded and the error is reported once its `END` arrives,
+/// so the decoder is always resynchronized on the next packet.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    len: usize,
+    escaped: bool,
+    error: Option<DecodeError>,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            escaped: false,
+            error: None,
+        }
+    }
+
+    /// Drops any partially decoded packet.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Feeds one byte, storing payload into `dst`.
+    ///
+    /// `dst` must be the same buffer for every byte of a packet. Returns
+    /// `Ok(Some(len))` once a complete packet of `len` bytes is in `dst`.
+    pub fn feed(&mut self, byte: u8, dst: &mut [u8]) -> Result<Option<usize>> {
+        if byte == END {
+            let res = match self.error {
+                Some(e) => Err(e),
+                None if self.len == 0 => Ok(None),
+                None => Ok(Some(self.len)),
+            };
+            self.reset();
+            return res;
+        }
+        if self.error.is_some() {
+            return Ok(None);
+        }
+
+        let byte = if self.escaped {
+            self.escaped = false;
+            match byte {
+                ESC_END => END,
+                ESC_ESC => ESC,
+                _ => {
+                    self.error = Some(DecodeError::BadEscape);
+                    return Ok(None);
+                }
+            }
+        } else if byte == ESC {
+            self.escaped = true;
+            return Ok(None);
+        } else {
+            byte
+        };
+
+        if self.len == dst.len() {
+            self.error = Some(DecodeError::Overflow);
+            return Ok(None);
+        }
+        dst[self.len] = byte;
+        self.len += 1;
+        Ok(None)
+    }
+}
+
+/// Decodes the first packet in `src` into `dst` and returns its length.
+///
+/// A packet that is not terminated by `END` is returned as is.
+pub fn decode(src: &[u8], dst: &mut [u8]) -> Result<usize> {
+    let mut decoder = Decoder::new();
+    for &byte in src {
+        if let Some(len) = decoder.feed(byte, dst)? {
+            return Ok(len);
+        }
+    }
+    decoder.feed(END, dst).map(|len| len.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let mut dst = [0u8; max_encoded_len(4)];
+        let n = encode(&[0x01, END, ESC, 0x02], &mut dst);
+        assert_eq!(
+            &dst[..n],
+            &[END, 0x01, ESC, ESC_END, ESC, ESC_ESC, 0x02, END]
+        );
+
+        let n = encode(&[], &mut dst);
+        assert_eq!(&dst[..n], &[END, END]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let raw: Vec<u8> = (0..=255).collect();
+        let mut enc = vec![0u8; max_encoded_len(raw.len())];
+        let n = encode(&raw, &mut enc);
+        assert!(!enc[1..n - 1].contains(&END));
+
+        let mut dec = vec![0u8; raw.len()];
+        assert_eq!(decode(&enc[..n], &mut dec), Ok(raw.len()));
+        assert_eq!(dec, raw);
+    }
+
+    #[test]
+    fn test_decoder_stream() {
+        let wire = [END, END, 0x11, ESC, ESC_END, END, 0x22, END];
+        let mut dec = [0u8; 4];
+        let mut decoder = Decoder::new();
+        let mut packets = Vec::new();
+        for byte in wire {
+            if let Some(len) = decoder.feed(byte, &mut dec).unwrap() {
+                packets.push(dec[..len].to_vec());
+            }
+        }
+        assert_eq!(packets, vec![vec![0x11, END], vec![0x22]]);
+    }
+
+    #[test]
+    fn test_bad_escape() {
+        let mut dec = [0u8; 4];
+        assert_eq!(
+            decode(&[0x11, ESC, 0x22, END], &mut dec),
+            Err(DecodeError::BadEscape)
+        );
+        assert_eq!(
+            decode(&[ESC, ESC, END], &mut dec),
+            Err(DecodeError::BadEscape)
+        );
+    }
+
+    #[test]
+    fn test_overflow_resync() {
+        let wire = [0x01, 0x02, 0x03, END, 0x04, END];
+        let mut dec = [0u8; 2];
+        let mut decoder = Decoder::new();
+        let results: Vec<_> = wire
+            .iter()
+            .map(|&b| decoder.feed(b, &mut dec))
+            .filter(|r| !matches!(r, Ok(None)))
+            .collect();
+        assert_eq!(results, vec![Err(DecodeError::Overflow), Ok(Some(1))]);
+        assert_eq!(dec[0], 0x04);
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A fair spin lock that grants access in FIFO order.
+//!
+//! [`RwLock`](crate::tinyrwlock::RwLock) lets whichever waiter wins the
+//! compare-exchange take the lock, so under continuous contention a waiter
+//! can starve. With a ticket lock each waiter takes a ticket and waits until
+//! it is served, so it waits for at most the waiters that arrived before it.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+pub struct TicketLock<T: ?Sized> {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+pub struct TicketLockGuard<'a, T: ?Sized + 'a> {
+    lock: &'a TicketLock<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for TicketLock<T> {}
+unsafe impl<T: ?Sized + Send> Sync for TicketLock<T> {}
+
+unsafe impl<T: ?Sized + Sync> Sync for TicketLockGuard<'_, T> {}
+
+impl<T> TicketLock<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            next_ticket: AtomicUsize::new(0),
+            now_serving: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> TicketLock<T> {
+    pub fn lock(&self) -> TicketLockGuard<'_, T> {
+        self.lock_with_ticket().1
+    }
+
+    fn lock_with_ticket(&self) -> (usize, TicketLockGuard<'_, T>) {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            core::hint::spin_loop();
+        }
+        (ticket, TicketLockGuard { lock: self })
+    }
+
+    /// Takes the lock only if nobody holds or waits for it.
+    pub fn try_lock(&self) -> Option<TicketLockGuard<'_, T>> {
+        let ticket = self.now_serving.load(Ordering::Relaxed);
+        self.next_ticket
+            .compare_exchange(
+                ticket,
+                ticket.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .ok()
+            .map(|_| TicketLockGuard { lock: self })
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.next_ticket.load(Ordering::Relaxed) != self.now_serving.load(Ordering::Relaxed)
+    }
+
+    /// Number of threads holding or waiting for the lock.
+    pub fn queue_len(&self) -> usize {
+        self.next_ticket
+            .load(Ordering::Relaxed)
+            .wrapping_sub(self.now_serving.load(Ordering::Relaxed))
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for TicketLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TicketLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_lock() {
+            Some(guard) => write!(f, "TicketLock {{ data: ")
+                .and_then(|()| (*guard).fmt(f))
+                .and_then(|()| write!(f, " }}")),
+            None => write!(f, "TicketLock {{ <locked> }}"),
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for TicketLockGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for TicketLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for TicketLockGuard<'_, T> {
+    fn drop(&mut self) {
+        // Only the holder advances `now_serving`.
+        let next = self
+            .lock
+            .now_serving
+            .load(Ordering::Relaxed)
+            .wrapping_add(1);
+        self.lock.now_serving.store(next, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread, vec::Vec};
+
+    type TicketLock<T> = super::TicketLock<T>;
+
+    #[test]
+    fn smoke() {
+        let l = TicketLock::new(0);
+        *l.lock() += 1;
+        assert!(!l.is_locked());
+        let g = l.try_lock().unwrap();
+        assert!(l.try_lock().is_none());
+        assert_eq!(l.queue_len(), 1);
+        drop(g);
+        assert_eq!(l.into_inner(), 1);
+    }
+
+    #[test]
+    fn fifo_order() {
+        let lock = Arc::new(TicketLock::new(Vec::new()));
+        let guard = lock.lock();
+        let mut handles = Vec::new();
+        for i in 0..4 {
+            let lock2 = lock.clone();
+            handles.push(thread::spawn(move || lock2.lock().push(i)));
+            // Wait until the thread has taken its ticket, so that tickets
+            // are handed out in spawn order.
+            while lock.queue_len() != i + 2 {
+                thread::yield_now();
+            }
+        }
+        drop(guard);
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(*lock.lock(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn bounded_wait() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 1000;
+        // The lock protects the number of acquisitions so far. Being served
+        // strictly in ticket order means a waiter is overtaken only by the
+        // at most THREADS - 1 tickets taken before its own.
+        let lock = Arc::new(TicketLock::new(0usize));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..ROUNDS {
+                        let (ticket, mut acquired) = lock.lock_with_ticket();
+                        assert_eq!(*acquired, ticket);
+                        *acquired += 1;
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(*lock.lock(), THREADS * ROUNDS);
+    }
+}
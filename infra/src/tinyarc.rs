@@ -13,6 +13,8 @@
 // limitations under the License.
 
 extern crate alloc;
+#[cfg(debug_assertions)]
+use crate::list::typed_atomic_ilist::ListCorruption;
 use crate::{
     intrusive::Adapter,
     list::{
@@ -369,6 +371,36 @@ impl<T: Sized, A: Adapter<T>> TinyArcList<T, A> {
         true
     }
 
+    /// Whether `me` is on the list running from `head` to `tail`, or to its
+    /// end if `tail` is `None`. Walks the list, meant for debug checks.
+    pub fn is_linked_in(
+        me: &TinyArc<T>,
+        head: &AtomicListHead<T, A>,
+        tail: Option<NonNull<AtomicListHead<T, A>>>,
+    ) -> bool {
+        let me_node = unsafe { (&**me as *const T as *const u8).add(A::offset()) };
+        ListIterator::new(head, tail).any(|node| node.as_ptr() as *const u8 == me_node)
+    }
+
+    /// Moves `me` from `from` to the back of `to`. Taking both lists mutably
+    /// proves both of their locks are held for the whole transfer, so `me` is
+    /// always on one of them. `me` must be on `from`. Returns false if `me`
+    /// is not on a list.
+    pub fn move_to(me: &mut TinyArc<T>, from: &mut Self, to: &mut Self) -> bool {
+        debug_assert!(
+            unsafe { Self::list_head_of_mut_unchecked(me) }.is_detached()
+                || Self::is_linked_in(me, &from.head, Some(NonNull::from_ref(&from.tail))),
+            "move_to: the node is on another list than `from`"
+        );
+        let me_node = unsafe { Self::list_head_of_mut_unchecked(me) };
+        if !AtomicListHead::<T, A>::detach(me_node) {
+            return false;
+        }
+        let ok = AtomicListHead::<T, A>::insert_before(&mut to.tail, me_node);
+        debug_assert!(ok);
+        true
+    }
+
     pub fn clear(&mut self) -> usize {
         let mut c = 0;
         for mut i in
@@ -380,6 +412,22 @@ impl<T: Sized, A: Adapter<T>> TinyArcList<T, A> {
         c
     }
 
+    /// Calls `visit` on every element in order, without touching the
+    /// reference counts. Meant for dumping a list while debugging.
+    #[cfg(debug_assertions)]
+    pub fn visit<F: FnMut(usize, &T)>(&self, mut visit: F) {
+        let tail = NonNull::from_ref(&self.tail);
+        for (i, node) in ListIterator::new(&self.head, Some(tail)).enumerate() {
+            visit(i, unsafe { node.as_ref() }.owner());
+        }
+    }
+
+    /// Checks the links of the whole list and returns its length.
+    #[cfg(debug_assertions)]
+    pub fn check(&self) -> Result<usize, ListCorruption> {
+        AtomicListHead::<T, A>::check_chain(&self.head, &self.tail)
+    }
+
     pub fn iter(&self) -> TinyArcListIterator<T, A> {
         TinyArcListIterator::<T, A>::new(&self.head, Some(NonNull::from_ref(&self.tail)))
     }
@@ -752,6 +800,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_move_to() {
+        let mut from = ControlStatusList::default();
+        from.init();
+        let mut to = ControlStatusList::default();
+        to.init();
+        let mut t = TinyArc::new(Thread::new(1));
+        from.push_back(t.clone());
+        to.push_back(TinyArc::new(Thread::new(0)));
+        assert_eq!(TinyArc::strong_count(&t), 2);
+
+        assert!(ControlStatusList::move_to(&mut t, &mut from, &mut to));
+        assert!(from.is_empty());
+        assert_eq!(TinyArc::strong_count(&t), 2);
+        let ids: Vec<_> = to.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [0, 1]);
+
+        let mut detached = TinyArc::new(Thread::new(2));
+        assert!(!ControlStatusList::move_to(
+            &mut detached,
+            &mut from,
+            &mut to
+        ));
+        to.clear();
+        assert_eq!(TinyArc::strong_count(&t), 1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_visit_and_check() {
+        let mut l = ControlStatusList::default();
+        l.init();
+        assert_eq!(l.check(), Ok(0));
+        for i in 0..3 {
+            l.push_back(TinyArc::new(Thread::new(i)));
+        }
+        let mut seen = Vec::new();
+        l.visit(|i, t| seen.push((i, t.id)));
+        assert_eq!(seen, [(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(l.check(), Ok(3));
+        l.clear();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "another list")]
+    fn test_move_to_from_wrong_list() {
+        let mut from = ControlStatusList::default();
+        from.init();
+        let mut other = ControlStatusList::default();
+        other.init();
+        let mut to = ControlStatusList::default();
+        to.init();
+        let mut t = TinyArc::new(Thread::new(1));
+        other.push_back(t.clone());
+        ControlStatusList::move_to(&mut t, &mut from, &mut to);
+    }
+
     #[test]
     fn test_push_and_pop() {
         type L = <ControlStatusList as GenericList>::Node;
@@ -174,6 +174,29 @@ pub(crate) fn get_max_free_block_size() -> usize {
     unsafe { HEAP.get_max_free_block_size() }
 }
 
+/// Heap fragmentation in percent, the share of free memory lying outside the
+/// largest free block.
+///
+/// 0 means all free memory is one block. A value rising towards 100 over the
+/// uptime means large allocations start failing while plenty of memory is
+/// still free, and long-lived objects should be allocated earlier or the
+/// heap be sized larger.
+pub fn fragmentation() -> usize {
+    let info = memory_info();
+    fragmentation_of(
+        info.total.saturating_sub(info.used),
+        get_max_free_block_size(),
+    )
+}
+
+fn fragmentation_of(free: usize, largest: usize) -> usize {
+    if free == 0 {
+        return 0;
+    }
+    let largest = largest.min(free);
+    (free - largest) * 100 / free
+}
+
 /// Returns the offset of the address within the alignment.
 ///
 /// Equivalent to `addr % align`, but the alignment must be a power of two.
@@ -214,6 +237,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fragmentation_of_test() {
+        assert_eq!(fragmentation_of(0, 0), 0);
+        assert_eq!(fragmentation_of(4096, 4096), 0);
+        assert_eq!(fragmentation_of(4096, 1024), 75);
+        assert_eq!(fragmentation_of(4096, 0), 100);
+        // The largest block is read separately and may be stale.
+        assert_eq!(fragmentation_of(1024, 4096), 0);
+    }
+
     #[test]
     fn basic_allocation_and_deallocation() {
         // Test basic allocation and deallocation
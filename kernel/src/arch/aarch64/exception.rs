@@ -208,6 +208,8 @@ extern "C" fn trap_fiq(context: &mut Context) -> usize {
 }
 
 fn show_exception(ec: u64, context: &mut Context) {
+    #[cfg(event_trace)]
+    crate::trace::dump_all();
     match ec {
         0x00 => panic!("Unknow reason Exceptions\n======== error stack ======== \n{}",context),
         0x01 => panic!("WFI or WFE instruction\n======== error stack ======== \n{}",context),
@@ -213,8 +213,32 @@ impl fmt::Display for HardFaultRegs {
     }
 }
 
-pub extern "C" fn panic_on_hardfault(ctx: &IsrContext) {
+// A semihosting call without a debugger attached raises a debug event that
+// escalates to a HardFault. Resume after the `bkpt 0xab` as if the host had
+// failed the call.
+fn skip_semihosting_call(ctx: &mut IsrContext) -> bool {
+    const HFSR_DEBUGEVT: u32 = 1 << 31;
+    const BKPT_SEMIHOSTING: u16 = 0xBEAB;
+    let scb = unsafe { &*SCB::PTR };
+    if scb.hfsr.read() & HFSR_DEBUGEVT == 0
+        || unsafe { (ctx.pc as *const u16).read() } != BKPT_SEMIHOSTING
+    {
+        return false;
+    }
+    // HFSR bits are write-one-to-clear.
+    unsafe { scb.hfsr.write(HFSR_DEBUGEVT) };
+    ctx.pc += 2;
+    ctx.r0 = usize::MAX;
+    true
+}
+
+pub extern "C" fn panic_on_hardfault(ctx: &mut IsrContext) {
+    if skip_semihosting_call(ctx) {
+        return;
+    }
     super::disable_local_irq();
+    #[cfg(event_trace)]
+    crate::trace::dump_all();
     let fault_regs: HardFaultRegs = HardFaultRegs::from_scb();
     let xpsr = xpsr::read();
     panic!(
@@ -237,7 +261,9 @@ pub(crate) unsafe extern "C" fn handle_hardfault() {
         beq 1f
         mrs r0, psp
         1:
+        push {{r4, lr}}
         bl {panic}
+        pop {{r4, pc}}
         ",
         panic = sym panic_on_hardfault
     )
@@ -12,7 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use cortex_m::{interrupt::InterruptNumber, peripheral::scb::SystemHandler, Peripherals};
+use crate::sync::spinlock::SpinLock;
+use blueos_hal::HandlesIrq;
+use cortex_m::{
+    interrupt::InterruptNumber,
+    peripheral::{
+        scb::{SystemHandler, VectActive},
+        SCB,
+    },
+    Peripherals,
+};
 
 #[cfg(irq_priority_bits_2)]
 pub const IRQ_PRIORITY_STEP: u8 = 0x40;
@@ -166,3 +175,47 @@ pub const INTERRUPT_TABLE_LEN: usize = 240;
 #[cfg(armv8m)]
 pub const INTERRUPT_TABLE_LEN: usize = 496;
 pub type InterruptTable = [Vector; INTERRUPT_TABLE_LEN];
+
+/// A device serving its own interrupt, see [`register_handler`].
+pub trait IrqHandler: Send + Sync {
+    fn handle(&self);
+}
+
+impl<T: HandlesIrq> IrqHandler for T {
+    fn handle(&self) {
+        self.handle_irq();
+    }
+}
+
+static HANDLERS: SpinLock<[Option<&'static dyn IrqHandler>; INTERRUPT_TABLE_LEN]> =
+    SpinLock::new([None; INTERRUPT_TABLE_LEN]);
+
+/// Routes `irq` to `handler`. The vector table entry of `irq` must be
+/// [`handle_device_irq`], so that several instances of a peripheral share
+/// one handler instead of each needing its own vector function.
+pub fn register_handler(
+    irq: IrqNumber,
+    handler: &'static dyn IrqHandler,
+) -> Result<(), &'static str> {
+    let mut handlers = HANDLERS.irqsave_lock();
+    let slot = handlers
+        .get_mut(usize::from(irq))
+        .ok_or("IRQ number out of range")?;
+    *slot = Some(handler);
+    Ok(())
+}
+
+/// Vector table entry dispatching the active interrupt to the handler
+/// registered for it with [`register_handler`].
+pub unsafe extern "C" fn handle_device_irq() {
+    let VectActive::Interrupt { irqn } = SCB::vect_active() else {
+        return;
+    };
+    let irq = IrqNumber::new(u16::from(irqn));
+    let _trace = crate::irq::IrqTrace::new(irq);
+    // Not held while handling, a nested interrupt may dispatch too.
+    let handler = HANDLERS.irqsave_lock()[usize::from(irq)];
+    if let Some(handler) = handler {
+        handler.handle();
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Debug output through the ITM stimulus ports, sent over SWO.
+//!
+//! This gives a debug channel that does not need the console UART. The SWO
+//! pin runs asynchronously at `ITM_SWO_BAUDRATE` in NRZ (UART) encoding, and
+//! the debug probe must be set to the same rate, e.g. with OpenOCD:
+//!
+//! ```text
+//! tpiu config internal swo.log uart off <cpu_hz> <ITM_SWO_BAUDRATE>
+//! itm port 0 on
+//! ```
+//!
+//! The board has to route the SWO signal to its trace pin, and `init` must be
+//! given the frequency of the trace clock, which is the core clock on the
+//! supported boards. Writes are dropped while ITM or the port is disabled,
+//! so logging costs little when no probe listens.
+
+use crate::{logger::LogSink, support::DisableInterruptGuard};
+use blueos_kconfig::ITM_SWO_BAUDRATE;
+use core::fmt::{self, Write};
+use log::Record;
+
+const DEMCR: *mut u32 = 0xE000_EDFC as *mut u32;
+const DEMCR_TRCENA: u32 = 1 << 24;
+
+const ITM_BASE: usize = 0xE000_0000;
+const ITM_TER: *mut u32 = (ITM_BASE + 0xE00) as *mut u32;
+const ITM_TCR: *mut u32 = (ITM_BASE + 0xE80) as *mut u32;
+const ITM_LAR: *mut u32 = (ITM_BASE + 0xFB0) as *mut u32;
+const ITM_TCR_ITMENA: u32 = 1 << 0;
+const ITM_TCR_SYNCENA: u32 = 1 << 2;
+const ITM_TCR_TRACE_BUS_ID_1: u32 = 1 << 16;
+const ITM_LAR_UNLOCK: u32 = 0xC5AC_CE55;
+
+const TPIU_BASE: usize = 0xE004_0000;
+const TPIU_CSPSR: *mut u32 = (TPIU_BASE + 0x004) as *mut u32;
+const TPIU_ACPR: *mut u32 = (TPIU_BASE + 0x010) as *mut u32;
+const TPIU_SPPR: *mut u32 = (TPIU_BASE + 0x0F0) as *mut u32;
+const TPIU_FFCR: *mut u32 = (TPIU_BASE + 0x304) as *mut u32;
+// Asynchronous SWO, NRZ encoding.
+const TPIU_SPPR_NRZ: u32 = 2;
+
+pub const NUM_PORTS: u8 = 32;
+
+#[inline]
+fn stim(port: u8) -> *mut u32 {
+    (ITM_BASE + 4 * port as usize) as *mut u32
+}
+
+/// Enables trace, sets the TPIU up for SWO at `ITM_SWO_BAUDRATE` and
+/// enables stimulus port 0, which [`ItmSink`] writes to.
+pub fn init(trace_clk_hz: u32) {
+    let divider = trace_clk_hz
+        .checked_div(ITM_SWO_BAUDRATE as u32)
+        .unwrap_or(0);
+    assert!(
+        divider != 0,
+        "ITM_SWO_BAUDRATE {} must be between 1 and the trace clock, {} Hz",
+        ITM_SWO_BAUDRATE,
+        trace_clk_hz
+    );
+    let _dig = DisableInterruptGuard::new();
+    unsafe {
+        DEMCR.write_volatile(DEMCR.read_volatile() | DEMCR_TRCENA);
+        TPIU_CSPSR.write_volatile(1);
+        TPIU_ACPR.write_volatile(divider - 1);
+        TPIU_SPPR.write_volatile(TPIU_SPPR_NRZ);
+        // Bypass the formatter, ITM is the only trace source.
+        TPIU_FFCR.write_volatile(0);
+        ITM_LAR.write_volatile(ITM_LAR_UNLOCK);
+        ITM_TCR.write_volatile(ITM_TCR_ITMENA | ITM_TCR_SYNCENA | ITM_TCR_TRACE_BUS_ID_1);
+        ITM_TER.write_volatile(ITM_TER.read_volatile() | 1);
+    }
+}
+
+pub fn is_port_enabled(port: u8) -> bool {
+    debug_assert!(port < NUM_PORTS);
+    unsafe {
+        ITM_TCR.read_volatile() & ITM_TCR_ITMENA != 0 && ITM_TER.read_volatile() & (1 << port) != 0
+    }
+}
+
+/// Writes `buf` to stimulus `port`, busy-waiting while its FIFO is full.
+pub fn itm_write(port: u8, buf: &[u8]) {
+    if !is_port_enabled(port) {
+        return;
+    }
+    let stim = stim(port);
+    // Keep the bytes of one write together.
+    let _dig = DisableInterruptGuard::new();
+    let words = buf.chunks_exact(4);
+    let tail = words.remainder();
+    for word in words {
+        unsafe {
+            // Bit 0 reads as 1 when the port can take another write.
+            while stim.read_volatile() & 1 == 0 {}
+            stim.write_volatile(u32::from_le_bytes(word.try_into().unwrap()));
+        }
+    }
+    for &byte in tail {
+        unsafe {
+            while stim.read_volatile() & 1 == 0 {}
+            (stim as *mut u8).write_volatile(byte);
+        }
+    }
+}
+
+struct ItmWriter(u8);
+
+impl Write for ItmWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        itm_write(self.0, s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Log sink writing records to stimulus port 0, register it with
+/// [`add_sink`](crate::logger::add_sink).
+pub struct ItmSink;
+
+impl LogSink for ItmSink {
+    fn log(&self, record: &Record) {
+        let _ = writeln!(ItmWriter(0), "[{}] {}", record.level(), record.args());
+    }
+}
@@ -14,6 +14,12 @@
 
 pub(crate) mod hardfault;
 pub mod irq;
+#[cfg(itm)]
+pub mod itm;
+#[cfg(rtt)]
+pub mod rtt;
+#[cfg(semihosting_log)]
+pub mod semihosting;
 pub(crate) mod xpsr;
 use crate::{
     scheduler,
@@ -0,0 +1,215 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Debug I/O through SEGGER RTT.
+//!
+//! RTT keeps a control block and ring buffers in RAM that the debug probe
+//! reads and writes over SWD while the core runs, so it needs neither a UART
+//! nor a trace pin and is much faster than SWO. The probe finds the control
+//! block by the `_SEGGER_RTT` symbol or by scanning RAM for its ID, e.g.
+//! with OpenOCD:
+//!
+//! ```text
+//! rtt setup 0x20000000 0x80000 "SEGGER RTT"
+//! rtt start
+//! rtt server start 9090 0
+//! ```
+//!
+//! Up channel 0 carries the output and never blocks: what does not fit is
+//! dropped, so logging costs little when no probe listens. Down channel 0
+//! carries input from the host, read with [`read`].
+
+use crate::{logger::LogSink, support::DisableInterruptGuard};
+use blueos_kconfig::{RTT_DOWN_BUFFER_SIZE, RTT_UP_BUFFER_SIZE};
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Write},
+    sync::atomic::{compiler_fence, AtomicU32, Ordering},
+};
+use log::Record;
+
+#[cfg(use_defmt)]
+compile_error!("RTT conflicts with defmt-rtt, which defines its own _SEGGER_RTT");
+
+const ID: &[u8; 16] = b"SEGGER RTT\0\0\0\0\0\0";
+const NAME: &[u8] = b"Terminal\0";
+// Drop the part of a write that does not fit.
+const MODE_NO_BLOCK_TRIM: u32 = 1;
+
+#[repr(C)]
+struct Channel {
+    name: usize,
+    buffer: usize,
+    size: u32,
+    // Offsets into the buffer, WrOff is advanced by the producer and RdOff
+    // by the consumer, one of which is the probe.
+    write: AtomicU32,
+    read: AtomicU32,
+    flags: u32,
+}
+
+impl Channel {
+    const fn new() -> Self {
+        Self {
+            name: 0,
+            buffer: 0,
+            size: 0,
+            write: AtomicU32::new(0),
+            read: AtomicU32::new(0),
+            flags: 0,
+        }
+    }
+}
+
+#[repr(C)]
+struct ControlBlock {
+    id: [u8; 16],
+    max_up: u32,
+    max_down: u32,
+    up: Channel,
+    down: Channel,
+}
+
+#[repr(C)]
+struct Rtt {
+    cb: ControlBlock,
+    up_buf: [u8; RTT_UP_BUFFER_SIZE],
+    down_buf: [u8; RTT_DOWN_BUFFER_SIZE],
+}
+
+struct RttCell(UnsafeCell<Rtt>);
+
+// SAFETY: Only `init` writes the control block outside of the offsets, and
+// the ring buffer accesses are serialized with interrupts disabled.
+unsafe impl Sync for RttCell {}
+
+#[no_mangle]
+static _SEGGER_RTT: RttCell = RttCell(UnsafeCell::new(Rtt {
+    cb: ControlBlock {
+        id: [0; 16],
+        max_up: 1,
+        max_down: 1,
+        up: Channel::new(),
+        down: Channel::new(),
+    },
+    up_buf: [0; RTT_UP_BUFFER_SIZE],
+    down_buf: [0; RTT_DOWN_BUFFER_SIZE],
+}));
+
+fn rtt() -> &'static Rtt {
+    unsafe { &*_SEGGER_RTT.0.get() }
+}
+
+/// Sets the channels up and then publishes the control block. Output
+/// before this is dropped.
+pub fn init() {
+    let _dig = DisableInterruptGuard::new();
+    unsafe {
+        let rtt = &mut *_SEGGER_RTT.0.get();
+        rtt.cb.up.name = NAME.as_ptr() as usize;
+        rtt.cb.up.buffer = rtt.up_buf.as_mut_ptr() as usize;
+        rtt.cb.up.size = RTT_UP_BUFFER_SIZE as u32;
+        rtt.cb.up.flags = MODE_NO_BLOCK_TRIM;
+        rtt.cb.down.name = NAME.as_ptr() as usize;
+        rtt.cb.down.buffer = rtt.down_buf.as_mut_ptr() as usize;
+        rtt.cb.down.size = RTT_DOWN_BUFFER_SIZE as u32;
+        // The ID goes last, so the probe never finds a half-initialized
+        // block.
+        compiler_fence(Ordering::SeqCst);
+        for (i, &b) in ID.iter().enumerate() {
+            core::ptr::write_volatile(&mut rtt.cb.id[i], b);
+        }
+    }
+}
+
+fn is_initialized() -> bool {
+    rtt().cb.up.size != 0
+}
+
+/// Writes as much of `buf` as fits into up channel 0 and returns the number
+/// of bytes written.
+pub fn write(buf: &[u8]) -> usize {
+    if !is_initialized() {
+        return 0;
+    }
+    let rtt = rtt();
+    let ch = &rtt.cb.up;
+    let size = ch.size as usize;
+    // Keep the bytes of one write together.
+    let _dig = DisableInterruptGuard::new();
+    let rd = ch.read.load(Ordering::Acquire) as usize;
+    let mut wr = ch.write.load(Ordering::Relaxed) as usize;
+    // One slot stays free to tell a full buffer from an empty one.
+    let free = if rd > wr {
+        rd - wr - 1
+    } else {
+        size - wr + rd - 1
+    };
+    let n = buf.len().min(free);
+    let base = ch.buffer as *mut u8;
+    for &b in &buf[..n] {
+        unsafe { base.add(wr).write_volatile(b) };
+        wr += 1;
+        if wr == size {
+            wr = 0;
+        }
+    }
+    ch.write.store(wr as u32, Ordering::Release);
+    n
+}
+
+/// Reads what the host has sent on down channel 0 into `buf` and returns the
+/// number of bytes read.
+pub fn read(buf: &mut [u8]) -> usize {
+    if !is_initialized() {
+        return 0;
+    }
+    let rtt = rtt();
+    let ch = &rtt.cb.down;
+    let size = ch.size as usize;
+    let _dig = DisableInterruptGuard::new();
+    let wr = ch.write.load(Ordering::Acquire) as usize;
+    let mut rd = ch.read.load(Ordering::Relaxed) as usize;
+    let base = ch.buffer as *const u8;
+    let mut n = 0;
+    while rd != wr && n < buf.len() {
+        buf[n] = unsafe { base.add(rd).read_volatile() };
+        n += 1;
+        rd += 1;
+        if rd == size {
+            rd = 0;
+        }
+    }
+    ch.read.store(rd as u32, Ordering::Release);
+    n
+}
+
+pub struct Writer;
+
+impl Write for Writer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Log sink writing records to up channel 0, register it with
+/// [`add_sink`](crate::logger::add_sink).
+pub struct RttSink;
+
+impl LogSink for RttSink {
+    fn log(&self, record: &Record) {
+        let _ = writeln!(Writer, "[{}] {}", record.level(), record.args());
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Output to the host console through semihosting, for QEMU (`-semihosting`)
+//! or a debugger. It needs no UART or clock setup, so it works from the
+//! first instruction on.
+//!
+//! Without a debugger attached the `bkpt 0xab` escalates to a HardFault,
+//! which skips the call, so the output is silently dropped.
+
+use crate::logger::LogSink;
+use core::fmt::{self, Write};
+use log::Record;
+
+const SYS_WRITE0: usize = 0x04;
+
+#[inline(always)]
+unsafe fn call(op: usize, arg: usize) -> usize {
+    let mut ret = op;
+    core::arch::asm!("bkpt 0xab", inout("r0") ret, in("r1") arg, options(nostack));
+    ret
+}
+
+/// Prints `s` on the host console. `s` must not contain NUL bytes.
+pub fn sys_write0(s: &str) {
+    // SYS_WRITE0 takes a NUL-terminated string.
+    let mut buf = [0u8; 64];
+    for chunk in s.as_bytes().chunks(buf.len() - 1) {
+        buf[..chunk.len()].copy_from_slice(chunk);
+        buf[chunk.len()] = 0;
+        unsafe { call(SYS_WRITE0, buf.as_ptr() as usize) };
+    }
+}
+
+pub struct Writer;
+
+impl Write for Writer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        sys_write0(s);
+        Ok(())
+    }
+}
+
+/// Log sink printing records on the host console, register it with
+/// [`add_sink`](crate::logger::add_sink).
+pub struct SemihostingSink;
+
+impl LogSink for SemihostingSink {
+    fn log(&self, record: &Record) {
+        let _ = writeln!(Writer, "[{}] {}", record.level(), record.args());
+    }
+}
@@ -117,13 +117,6 @@ crate::define_pin_states! {
 
 #[no_mangle]
 pub unsafe extern "C" fn uart0_handler() {
-    use blueos_hal::HasInterruptReg;
     let uart = get_device!(console_uart);
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
-    }
+    uart.handle_interrupt();
 }
@@ -12,13 +12,72 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Board support.
+//!
+//! Exactly one board module is compiled in, picked by `TARGET_BOARD` through
+//! `current_board_mod!`, and its public items are re-exported from here.
+//! The rest of the kernel only talks to the board through this module, so
+//! every board must provide the following:
+//!
+//! * `pub(crate) fn init()`, called first thing by `boot::init`. It brings up
+//!   clocks, calls `boot::init_runtime` and `boot::init_heap`, initializes
+//!   the interrupt controller and starts the system tick.
+//! * `PIN_STATES`, generated by `define_pin_states!`. Boards without pin
+//!   muxing use `define_pin_states!(None)`.
+//! * The device statics and the `get_device!` macro, generated by
+//!   `define_peripheral!`. At least `console_uart` must be defined, it has
+//!   to implement `blueos_hal::uart::Uart` and is registered as `ttyS0`.
+//!   Further UARTs are registered by `init` with `boot::register_serial`.
+//! * With `virtio` enabled, `DRAM_BASE` pointing at the device tree blob.
+//! * On RISC-V, `handle_plic_irq`, `current_cycles` and `set_timeout_after`,
+//!   used by the trap handler and the system tick.
+//!
+//! `define_board!` generates the last two from one description, together
+//! with the base addresses of the devices.
+//!
+//! `board_contract` fails the build with a pointed error when a board
+//! misses one of these, instead of an unresolved name deep inside `boot`.
+
 use blueos_macro::current_board_mod;
 
 current_board_mod!();
 
+// Never called, only type checked against the selected board.
+#[allow(dead_code)]
+fn board_contract() {
+    let _: fn() = init;
+    let _ = PIN_STATES;
+    let _ = get_device!(console_uart);
+    #[cfg(virtio)]
+    let _ = DRAM_BASE;
+    #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+    {
+        let _ = handle_plic_irq;
+        let _: fn() -> usize = current_cycles;
+        let _: fn(usize) = set_timeout_after;
+    }
+}
+
 #[macro_export]
 macro_rules! define_peripheral {
     ($( ($field_name:ident, $device_ty:ty, $v:expr) ),* $(,)?) => {
+        // Never used, it only exists so that a duplicated device name is
+        // reported against the name itself before the generated statics
+        // and `get_device!` arms collide.
+        #[allow(dead_code, non_camel_case_types)]
+        enum PeripheralNames {
+            $( $field_name, )*
+        }
+
+        pub(crate) const PERIPHERAL_COUNT: usize =
+            <[&str]>::len(&[$( stringify!($field_name) ),*]);
+
+        const _: () = assert!(
+            blueos_kconfig::MAX_PERIPHERAL_DEVICES == 0
+                || PERIPHERAL_COUNT <= blueos_kconfig::MAX_PERIPHERAL_DEVICES,
+            "define_peripheral!: more devices than MAX_PERIPHERAL_DEVICES"
+        );
+
         paste::paste! {
             $(
                 pub static [<$field_name:upper>]: $device_ty = $v;
@@ -50,3 +109,79 @@ macro_rules! define_pin_states {
         pub(crate) const PIN_STATES: &[&()] = &[];
     }
 }
+
+/// Describes a board in one place: its clocks, the MMIO regions of its
+/// devices, the devices themselves and the pin states.
+///
+/// Each clock `name: hz` becomes a `NAME_HZ` constant and each region
+/// `name: (base, size)` a `NAME_BASE` constant, for `init` and the device
+/// constructors to use. The optional `mmio_window: (start, end)` becomes
+/// `MMIO_WINDOW`. The build fails if two regions overlap, if a region lies
+/// outside the window or if a pin is given two states, which catches a
+/// mistyped number before it aliases another device or pin. The
+/// `peripherals` and `pins` parts are then passed on to `define_peripheral!`
+/// and `define_pin_states!`.
+#[macro_export]
+macro_rules! define_board {
+    (@window) => { (0, usize::MAX) };
+    (@window ($start:expr, $end:expr)) => { ($start, $end) };
+    (
+        clocks: { $( $clock:ident: $hz:expr ),* $(,)? },
+        $( mmio_window: ($window_start:expr, $window_end:expr), )?
+        mmio: { $( $region:ident: ($base:expr, $size:expr) ),* $(,)? },
+        peripherals: { $( $peripheral:tt ),* $(,)? },
+        pins: (
+            $pin_class:ty,
+            $( ( $pin:expr $(, $pin_arg:expr )* $(,)? ) ),* $(,)?
+        ) $(,)?
+    ) => {
+        paste::paste! {
+            $( pub(crate) const [<$clock:upper _HZ>]: u32 = $hz; )*
+            $( pub(crate) const [<$region:upper _BASE>]: usize = $base; )*
+        }
+
+        $( pub(crate) const MMIO_WINDOW: (usize, usize) = ($window_start, $window_end); )?
+
+        const _: () = {
+            let regions: &[(usize, usize)] = &[ $( ($base, $size) ),* ];
+            let window: (usize, usize) =
+                $crate::define_board!(@window $( ($window_start, $window_end) )?);
+            let mut i = 0;
+            while i < regions.len() {
+                let (base, size) = regions[i];
+                assert!(
+                    window.0 <= base && base + size <= window.1,
+                    "define_board!: MMIO region outside the MMIO window"
+                );
+                let mut j = i + 1;
+                while j < regions.len() {
+                    let (a, a_size) = regions[i];
+                    let (b, b_size) = regions[j];
+                    assert!(
+                        a + a_size <= b || b + b_size <= a,
+                        "define_board!: overlapping MMIO regions"
+                    );
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+
+        const _: () = {
+            let pins: &[usize] = &[ $( $pin as usize ),* ];
+            let mut i = 0;
+            while i < pins.len() {
+                let mut j = i + 1;
+                while j < pins.len() {
+                    assert!(pins[i] != pins[j], "define_board!: pin assigned twice");
+                    j += 1;
+                }
+                i += 1;
+            }
+        };
+
+        $crate::define_peripheral! { $( $peripheral ),* }
+
+        $crate::define_pin_states!( $pin_class, $( ( $pin $(, $pin_arg )* ) ),* );
+    };
+}
@@ -77,6 +77,11 @@ pub(crate) fn init() {
     }
     boot::init_runtime();
     unsafe { boot::init_heap() };
+    #[cfg(semihosting_log)]
+    crate::logger::add_sink(
+        &arch::semihosting::SemihostingSink,
+        crate::logger::LogLevel::Trace,
+    );
     arch::irq::init();
     time::systick_init(config::SYSTEM_CORE_CLOCK);
     arch::irq::enable_irq_with_priority(UART0RX_IRQn, arch::irq::Priority::Normal);
@@ -98,25 +103,13 @@ crate::define_peripheral! {
 #[no_mangle]
 pub unsafe extern "C" fn uart0rx_handler() {
     let uart = get_device!(console_uart);
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
-    }
+    uart.handle_interrupt();
     uart.clear_interrupt(blueos_driver::uart::InterruptType::Rx);
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn uart0tx_handler() {
     let uart = get_device!(console_uart);
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
-    }
+    uart.handle_interrupt();
     uart.clear_interrupt(blueos_driver::uart::InterruptType::Tx);
 }
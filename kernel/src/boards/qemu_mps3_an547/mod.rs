@@ -79,6 +79,8 @@ unsafe fn enable_fpu() {
     let mut temp = SCB_CPACR_PTR.read_volatile();
     temp |= 0x00F00000;
     SCB_CPACR_PTR.write_volatile(temp);
+    blueos_hal::barrier::dsb();
+    blueos_hal::barrier::isb();
 }
 
 pub(crate) fn init() {
@@ -89,6 +91,11 @@ pub(crate) fn init() {
     }
     boot::init_runtime();
     unsafe { boot::init_heap() };
+    #[cfg(semihosting_log)]
+    crate::logger::add_sink(
+        &arch::semihosting::SemihostingSink,
+        crate::logger::LogLevel::Trace,
+    );
     arch::irq::init();
     time::systick_init(config::SYSTEM_CORE_CLOCK);
     arch::irq::enable_irq_with_priority(UART0RX_IRQn, arch::irq::Priority::Normal);
@@ -110,24 +117,12 @@ crate::define_pin_states!(None);
 #[no_mangle]
 pub unsafe extern "C" fn uart0rx_handler() {
     let uart = get_device!(console_uart);
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
-    }
+    uart.handle_interrupt();
     uart.clear_interrupt(blueos_driver::uart::InterruptType::Rx);
 }
 #[no_mangle]
 pub unsafe extern "C" fn uart0tx_handler() {
     let uart = get_device!(console_uart);
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
-    }
+    uart.handle_interrupt();
     uart.clear_interrupt(blueos_driver::uart::InterruptType::Tx);
 }
@@ -82,11 +82,6 @@ pub struct Serial0Irq {}
 impl IrqHandler for Serial0Irq {
     fn handle(&mut self) {
         let uart = get_device!(console_uart);
-        if let Some(handler) = unsafe {
-            let intr_handler_cell = &*uart.intr_handler.get();
-            intr_handler_cell.as_ref()
-        } {
-            handler();
-        }
+        uart.handle_interrupt();
     }
 }
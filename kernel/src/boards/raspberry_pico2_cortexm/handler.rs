@@ -57,17 +57,14 @@ const fn build_exception_handlers() -> [Vector; 15] {
     tbl
 }
 
-unsafe extern "C" {
-    fn uart0_handler();
-}
-
 #[doc(hidden)]
 #[link_section = ".interrupt.handlers"]
 #[no_mangle]
 static __INTERRUPT_HANDLERS__: [Vector; 50] = {
     let mut tbl = [Vector { reserved: 0 }; 50];
+    // UART0. A UART1 at 34 would use the same entry.
     tbl[33] = Vector {
-        handler: uart0_handler,
+        handler: arch::irq::handle_device_irq,
     };
     tbl
 };
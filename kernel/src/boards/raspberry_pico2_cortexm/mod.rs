@@ -21,7 +21,8 @@ use crate::{
     boot::INIT_BSS_DONE,
     time,
 };
-use blueos_hal::clock_control::ClockControl;
+use blueos_driver::uart::arm_pl011::ArmPl011;
+use blueos_hal::{clock_control::ClockControl, HasInterruptReg};
 use core::ptr::addr_of;
 use spin::Once;
 
@@ -83,48 +84,72 @@ pub(crate) fn init() {
         temp |= SCB_CPACR_FULL_ACCESS << (4 * 2);
         temp |= 0x00F00000;
         SCB_CPACR_PTR.write_volatile(temp);
-        core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        blueos_hal::barrier::dsb();
+        blueos_hal::barrier::isb();
         copy_data();
     }
     boot::init_runtime();
-    blueos_driver::clock_control::rpi_pico::RpiPicoClockControl::init();
+    {
+        use blueos_driver::clock_control::rpi_pico::{Peripheral, RpiPicoClockControl};
+        RpiPicoClockControl::init();
+        // The pin states are applied through these before any driver runs.
+        RpiPicoClockControl::wait_peripheral_ready(Peripheral::IOBank0)
+            .expect("IO_BANK0 did not leave reset");
+        RpiPicoClockControl::wait_peripheral_ready(Peripheral::PadsBank0)
+            .expect("PADS_BANK0 did not leave reset");
+    }
 
     unsafe { boot::init_heap() };
     arch::irq::init();
-    arch::irq::enable_irq_with_priority(IrqNumber::new(33), arch::irq::Priority::Normal);
-    time::systick_init(150_000_000);
+    register_uart_irqs(get_device!(console_uart));
+    time::systick_init(SYS_HZ);
+    #[cfg(itm)]
+    {
+        arch::itm::init(SYS_HZ);
+        crate::logger::add_sink(&arch::itm::ItmSink, crate::logger::LogLevel::Trace);
+    }
+    #[cfg(rtt)]
+    {
+        arch::rtt::init();
+        crate::logger::add_sink(&arch::rtt::RttSink, crate::logger::LogLevel::Trace);
+    }
 }
 
-crate::define_peripheral! {
-    (console_uart, blueos_driver::uart::arm_pl011::ArmPl011<'static>,
-     blueos_driver::uart::arm_pl011::ArmPl011::<'static>::new(
-        0x40070000 as _,
-        150_000_000,
-        Some((get_device!(subsys_reset), 26)),
-     )),
-    (subsys_reset, blueos_driver::reset::rpi_pico_reset::RpiPicoReset,
-    blueos_driver::reset::rpi_pico_reset::RpiPicoReset::new(
-        0x40020000
-    )),
+// Each peripheral has 4 KiB of registers followed by the XOR, set and
+// clear aliases. The window is the APB and AHB peripheral space.
+crate::define_board! {
+    clocks: {
+        sys: 150_000_000,
+    },
+    mmio_window: (0x4000_0000, 0x6000_0000),
+    mmio: {
+        resets: (0x4002_0000, 0x4000),
+        uart0: (0x4007_0000, 0x4000),
+    },
+    peripherals: {
+        (console_uart, blueos_driver::uart::arm_pl011::ArmPl011<'static>,
+         blueos_driver::uart::arm_pl011::ArmPl011::<'static>::new_with(
+            blueos_driver::uart::arm_pl011::ArmPl011Config::new(UART0_BASE, SYS_HZ)
+                .with_reset(get_device!(subsys_reset), 26)
+                .with_mmio_window(MMIO_WINDOW.0, MMIO_WINDOW.1)
+                .with_irq(33),
+         )),
+        (subsys_reset, blueos_driver::reset::rpi_pico_reset::RpiPicoReset,
+         blueos_driver::reset::rpi_pico_reset::RpiPicoReset::new(RESETS_BASE)),
+    },
+    pins: (
+        blueos_driver::pinctrl::rpi_pico::RpiPicoPinctrl,
+        (2, 11), // GPIO2 as UART0_TX
+        (3, 11), // GPIO3 as UART0_RX
+    ),
 }
 
-crate::define_pin_states!(
-    blueos_driver::pinctrl::rpi_pico::RpiPicoPinctrl,
-    (2, 11), // GPIO2 as UART0_TX
-    (3, 11), // GPIO3 as UART0_RX
-);
-
-#[no_mangle]
-pub unsafe extern "C" fn uart0_handler() {
-    use blueos_hal::HasInterruptReg;
-    let uart = get_device!(console_uart);
-    let intr = uart.get_interrupt();
-    if let Some(handler) = unsafe {
-        let intr_handler_cell = &*uart.intr_handler.get();
-
-        intr_handler_cell.as_ref()
-    } {
-        handler();
+// The vector table sends every UART interrupt to `handle_device_irq`, which
+// finds the instance by its IRQ number.
+fn register_uart_irqs(uart: &'static ArmPl011<'static>) {
+    for &irq in uart.get_irq_nums() {
+        let irq = IrqNumber::new(irq as u16);
+        arch::irq::register_handler(irq, uart).unwrap();
+        arch::irq::enable_irq_with_priority(irq, arch::irq::Priority::Normal);
     }
-    uart.clear_interrupt(intr);
 }
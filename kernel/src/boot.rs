@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod xmodem;
+
 #[cfg(kernel_async)]
 use crate::asynk;
 #[cfg(enable_net)]
@@ -24,19 +26,23 @@ use crate::{
         console,
         tty::{
             n_tty::Tty,
-            serial::{uart::UartDevice, Serial},
+            serial::{uart::UartDevice, Serial, SerialError},
             termios::Termios,
         },
-        DeviceManager,
+        Device, DeviceManager,
     },
     logger, scheduler,
     sync::SpinLock,
     thread, time,
 };
-use alloc::{string::String, sync::Arc};
-use blueos_driver::uart::UartConfig;
-use blueos_hal::{Configuration, PlatPeri};
-use core::ptr::{addr_of, addr_of_mut};
+use alloc::sync::Arc;
+use blueos_driver::uart::{InterruptType, UartConfig, UartCtrlStatus};
+use blueos_hal::{uart::Uart, Configuration, PlatPeri};
+use core::{
+    ptr::{addr_of, addr_of_mut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use embedded_io::{ErrorType, Read, ReadReady, Write};
 use spin::Once;
 
 // We have to put these globals in the .data section. If not specified explicitly,
@@ -74,14 +80,95 @@ extern "C" {
     pub static mut _end: u8;
 }
 
-static SERIAL0: Once<Arc<Serial>> = Once::new();
+const MAX_SERIALS: usize = 4;
+
+static SERIALS: [Once<Arc<Serial>>; MAX_SERIALS] = [const { Once::new() }; MAX_SERIALS];
 
 pub fn get_serial(index: u32) -> &'static Arc<Serial> {
-    match index {
-        0 => SERIAL0
-            .get()
-            .expect("uart_init must be called before get_serial"),
-        _ => panic!("unsupported SERIAL number"),
+    SERIALS
+        .get(index as usize)
+        .and_then(Once::get)
+        .expect("serial device not registered")
+}
+
+/// Registers `uart` as `ttyS<index>`. `console_uart` is `ttyS0`, boards
+/// register their other UARTs from `init`, after `init_heap`.
+///
+/// The interrupt handler of the UART is given `index`, so each instance
+/// feeds its own serial device.
+pub fn register_serial<T>(index: u32, uart: &'static T) -> &'static Arc<Serial>
+where
+    T: Uart<UartConfig, (), InterruptType, UartCtrlStatus>,
+{
+    let slot = SERIALS
+        .get(index as usize)
+        .expect("serial index out of range");
+    assert!(slot.get().is_none(), "ttyS{} registered twice", index);
+    let serial = slot.call_once(|| {
+        Arc::new(Serial::new(
+            index,
+            Termios::default(),
+            Arc::new(SpinLock::new(UartDevice::new(uart, index as usize))),
+        ))
+    });
+    let _ = DeviceManager::get().register_device(serial.name(), serial.clone());
+    serial
+}
+
+static RAW_CONSOLE: AtomicBool = AtomicBool::new(false);
+
+/// Exclusive raw access to the console UART, see [`console_acquire_raw`].
+pub struct RawConsoleGuard {
+    serial: &'static Arc<Serial>,
+    log_level: log::LevelFilter,
+}
+
+/// Takes the console UART away from the TTY layer and the logger so that a
+/// raw protocol, e.g. [`xmodem`], sees every received byte. Reception through
+/// the TTY and log output are restored when the guard is dropped.
+pub fn console_acquire_raw() -> RawConsoleGuard {
+    assert!(
+        !RAW_CONSOLE.swap(true, Ordering::Acquire),
+        "console is already in raw mode"
+    );
+    let log_level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+    let serial = get_serial(0);
+    serial.suspend_rx();
+    RawConsoleGuard { serial, log_level }
+}
+
+impl Drop for RawConsoleGuard {
+    fn drop(&mut self) {
+        self.serial.resume_rx();
+        log::set_max_level(self.log_level);
+        RAW_CONSOLE.store(false, Ordering::Release);
+    }
+}
+
+impl ErrorType for RawConsoleGuard {
+    type Error = SerialError;
+}
+
+impl Read for RawConsoleGuard {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.serial.uart_ops.irqsave_lock().read(buf)
+    }
+}
+
+impl ReadReady for RawConsoleGuard {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.serial.uart_ops.irqsave_lock().read_ready()
+    }
+}
+
+impl Write for RawConsoleGuard {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.serial.uart_ops.irqsave_lock().write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.serial.uart_ops.irqsave_lock().flush()
     }
 }
 
@@ -101,17 +188,8 @@ extern "C" fn init() {
     uart.configure(&UartConfig::default()).unwrap();
     uart.enable();
 
-    SERIAL0.call_once(|| {
-        Arc::new(Serial::new(
-            0,
-            Termios::default(),
-            Arc::new(SpinLock::new(UartDevice::new(crate::boards::get_device!(
-                console_uart
-            )))),
-        ))
-    });
-    DeviceManager::get().register_device(String::from("ttyS0"), get_serial(0).clone());
-    match console::init_console(Tty::init(get_serial(0).clone()).clone()) {
+    let serial = register_serial(0, uart);
+    match console::init_console(Tty::init(serial.clone()).clone()) {
         Ok(_) => {}
         Err(err) => panic!("Failed to init console: {}", crate::error::Error::from(err)),
     }
@@ -206,11 +284,25 @@ fn init_apps() {
 
 #[inline(never)]
 pub(crate) fn init_heap() {
+    init_heap_region(addr_of_mut!(__heap_start), addr_of_mut!(__heap_end));
+}
+
+/// Initializes the heap on `[start, end)` instead of the whole linker heap
+/// region, e.g. to keep the rest of the RAM for DMA buffers. The region must
+/// lie within `__heap_start..__heap_end`.
+#[inline(never)]
+pub(crate) fn init_heap_region(start: *mut u8, end: *mut u8) {
     unsafe {
         if INIT_HEAP_DONE {
             return;
         }
-        allocator::init_heap(addr_of_mut!(__heap_start), addr_of_mut!(__heap_end));
+        assert!(
+            addr_of_mut!(__heap_start) <= start && start < end && end <= addr_of_mut!(__heap_end),
+            "heap region {:?}..{:?} is outside the linker heap",
+            start,
+            end
+        );
+        allocator::init_heap(start, end);
         INIT_HEAP_DONE = true;
     }
 }
@@ -0,0 +1,546 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! XMODEM-1K receiver with CRC-16 block checks.
+//!
+//! The receiver drives the handshake with `C` to request CRC mode, accepts
+//! both 128-byte (`SOH`) and 1024-byte (`STX`) blocks, NAKs damaged blocks and
+//! ACKs duplicates caused by a lost ACK. Payload is handed to a
+//! [`FirmwareSink`] as it arrives. XMODEM itself has no notion of image size
+//! and pads the last block, so the caller supplies the expected length and
+//! CRC of the image. Nothing is committed unless both match.
+
+use crate::time::{
+    source::{poll_until, SysTick, TimeSource},
+    Duration,
+};
+use alloc::vec;
+use blueos_infra::crc::CRC16_XMODEM;
+use embedded_io::{Read, ReadReady, Write};
+
+const SOH: u8 = 0x01;
+const STX: u8 = 0x02;
+const EOT: u8 = 0x04;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+const CAN: u8 = 0x18;
+const CRC_MODE: u8 = b'C';
+
+const SHORT_BLOCK_SIZE: usize = 128;
+const LONG_BLOCK_SIZE: usize = 1024;
+
+const MAX_RETRIES: usize = 10;
+// Interval between `C` while waiting for the sender to start.
+const START_TIMEOUT: Duration = Duration::from_millis(3000);
+// Time allowed for the next block header once the transfer is running.
+const BLOCK_TIMEOUT: Duration = Duration::from_millis(10_000);
+// Time allowed between two bytes of a block.
+const CHAR_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmodemError {
+    /// The underlying port reported an error.
+    Io,
+    /// The sender went silent.
+    Timeout,
+    /// The sender cancelled the transfer.
+    Cancelled,
+    /// A block arrived out of order.
+    Sequence,
+    /// Too many consecutive damaged or missing blocks.
+    TooManyRetries,
+    /// The image does not fit the sink, or the sender sent more than expected.
+    ImageTooLarge,
+    /// The received image does not match the expected length or CRC.
+    ImageMismatch,
+    /// The sink failed to store or commit the image.
+    Sink,
+}
+
+/// Length and CRC-16/XMODEM of the image the sender is expected to transfer.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageInfo {
+    pub len: usize,
+    pub crc: u16,
+}
+
+/// Destination of a received image, typically a flash update region.
+pub trait FirmwareSink {
+    type Error: core::fmt::Debug;
+
+    /// Size of the region in bytes.
+    fn capacity(&self) -> usize;
+    /// Stages `data` at `offset` within the region.
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error>;
+    /// Called once the whole image has been staged and verified.
+    fn commit(&mut self, len: usize) -> Result<(), Self::Error>;
+}
+
+/// CRC-16/XMODEM (polynomial 0x1021, initial value 0).
+pub fn crc16(crc: u16, data: &[u8]) -> u16 {
+    CRC16_XMODEM.update(crc, data)
+}
+
+fn read_byte<P, C>(port: &mut P, clock: &C, timeout: Duration) -> Result<u8, XmodemError>
+where
+    P: Read + ReadReady,
+    C: TimeSource + ?Sized,
+{
+    let mut res = Err(XmodemError::Timeout);
+    let mut byte = [0u8; 1];
+    poll_until(clock, timeout, || {
+        match port.read_ready() {
+            Ok(false) => return false,
+            Ok(true) => {}
+            Err(_) => {
+                res = Err(XmodemError::Io);
+                return true;
+            }
+        }
+        match port.read(&mut byte) {
+            Ok(1) => res = Ok(byte[0]),
+            Ok(_) => return false,
+            Err(_) => res = Err(XmodemError::Io),
+        }
+        true
+    });
+    res
+}
+
+fn send<P: Write>(port: &mut P, bytes: &[u8]) -> Result<(), XmodemError> {
+    port.write_all(bytes).map_err(|_| XmodemError::Io)?;
+    port.flush().map_err(|_| XmodemError::Io)
+}
+
+fn cancel<P: Write>(port: &mut P) {
+    let _ = send(port, &[CAN, CAN, CAN]);
+}
+
+// Drops whatever is left of a damaged block so the NAK lines up with the
+// sender's retransmission.
+fn purge<P, C>(port: &mut P, clock: &C) -> Result<(), XmodemError>
+where
+    P: Read + ReadReady,
+    C: TimeSource + ?Sized,
+{
+    loop {
+        match read_byte(port, clock, CHAR_TIMEOUT) {
+            Ok(_) => {}
+            Err(XmodemError::Timeout) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Reads the remainder of a block after its header. Returns the block number,
+// or `None` if the block is damaged.
+fn read_block<P, C>(port: &mut P, clock: &C, buf: &mut [u8]) -> Result<Option<u8>, XmodemError>
+where
+    P: Read + ReadReady,
+    C: TimeSource + ?Sized,
+{
+    let num = read_byte(port, clock, CHAR_TIMEOUT)?;
+    let inv = read_byte(port, clock, CHAR_TIMEOUT)?;
+    for byte in buf.iter_mut() {
+        *byte = read_byte(port, clock, CHAR_TIMEOUT)?;
+    }
+    let crc_hi = read_byte(port, clock, CHAR_TIMEOUT)?;
+    let crc_lo = read_byte(port, clock, CHAR_TIMEOUT)?;
+    if num != !inv || crc16(0, buf) != u16::from_be_bytes([crc_hi, crc_lo]) {
+        return Ok(None);
+    }
+    Ok(Some(num))
+}
+
+/// Receives an image from `port` into `sink` and commits it if it matches
+/// `expected`. Returns the committed length.
+///
+/// The port must be owned exclusively for the duration of the transfer, no
+/// other reader may consume bytes from it.
+pub fn receive<P, S>(port: &mut P, sink: &mut S, expected: &ImageInfo) -> Result<usize, XmodemError>
+where
+    P: Read + ReadReady + Write,
+    S: FirmwareSink,
+{
+    receive_with_clock(port, sink, expected, &SysTick)
+}
+
+/// [`receive`] with the timeouts measured on `clock`.
+pub fn receive_with_clock<P, S, C>(
+    port: &mut P,
+    sink: &mut S,
+    expected: &ImageInfo,
+    clock: &C,
+) -> Result<usize, XmodemError>
+where
+    P: Read + ReadReady + Write,
+    S: FirmwareSink,
+    C: TimeSource + ?Sized,
+{
+    receive_with_progress(port, sink, expected, clock, usize::MAX, |_| {})
+}
+
+/// [`receive_with_clock`] that calls `progress` with the number of bytes
+/// received so far each time another `interval` bytes have arrived, and
+/// once more when the sender ends the transfer.
+///
+/// `progress` runs in the receiving thread between two blocks, it must not
+/// write to `port` and should return quickly, the sender is waiting for the
+/// ACK.
+pub fn receive_with_progress<P, S, C>(
+    port: &mut P,
+    sink: &mut S,
+    expected: &ImageInfo,
+    clock: &C,
+    interval: usize,
+    mut progress: impl FnMut(usize),
+) -> Result<usize, XmodemError>
+where
+    P: Read + ReadReady + Write,
+    S: FirmwareSink,
+    C: TimeSource + ?Sized,
+{
+    assert!(interval > 0);
+    if expected.len > sink.capacity() {
+        return Err(XmodemError::ImageTooLarge);
+    }
+
+    let mut buf = vec![0u8; LONG_BLOCK_SIZE];
+    let mut seq: u8 = 1;
+    let mut offset = 0;
+    let mut reported = 0;
+    let mut crc = 0;
+    let mut retries = 0;
+    let mut started = false;
+
+    loop {
+        if retries > MAX_RETRIES {
+            cancel(port);
+            return Err(XmodemError::TooManyRetries);
+        }
+        if !started {
+            send(port, &[CRC_MODE])?;
+        }
+
+        let timeout = if started {
+            BLOCK_TIMEOUT
+        } else {
+            START_TIMEOUT
+        };
+        let header = match read_byte(port, clock, timeout) {
+            Ok(header) => header,
+            Err(XmodemError::Timeout) => {
+                retries += 1;
+                if started {
+                    send(port, &[NAK])?;
+                }
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let size = match header {
+            SOH => SHORT_BLOCK_SIZE,
+            STX => LONG_BLOCK_SIZE,
+            EOT => {
+                send(port, &[ACK])?;
+                if reported != offset {
+                    progress(offset);
+                }
+                break;
+            }
+            CAN => {
+                if read_byte(port, clock, CHAR_TIMEOUT) == Ok(CAN) {
+                    return Err(XmodemError::Cancelled);
+                }
+                continue;
+            }
+            // Line noise between blocks.
+            _ => continue,
+        };
+        started = true;
+
+        let block = &mut buf[..size];
+        let num = match read_block(port, clock, block) {
+            Ok(Some(num)) => num,
+            Ok(None) | Err(XmodemError::Timeout) => {
+                purge(port, clock)?;
+                send(port, &[NAK])?;
+                retries += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if num == seq.wrapping_sub(1) {
+            // Our ACK was lost and the sender repeated the previous block.
+            send(port, &[ACK])?;
+            continue;
+        }
+        if num != seq {
+            cancel(port);
+            return Err(XmodemError::Sequence);
+        }
+
+        let remaining = expected.len - offset;
+        if remaining == 0 {
+            cancel(port);
+            return Err(XmodemError::ImageTooLarge);
+        }
+        // Only the last block carries padding, drop it here.
+        let n = remaining.min(size);
+        if sink.write(offset, &block[..n]).is_err() {
+            cancel(port);
+            return Err(XmodemError::Sink);
+        }
+        crc = crc16(crc, &block[..n]);
+        offset += n;
+        if offset / interval > reported / interval {
+            progress(offset);
+            reported = offset;
+        }
+        seq = seq.wrapping_add(1);
+        retries = 0;
+        send(port, &[ACK])?;
+    }
+
+    if offset != expected.len || crc != expected.crc {
+        return Err(XmodemError::ImageMismatch);
+    }
+    sink.commit(offset).map_err(|_| XmodemError::Sink)?;
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::source::MockTime;
+    use alloc::{collections::VecDeque, vec::Vec};
+    use blueos_test_macro::test;
+    use embedded_io::{ErrorKind, ErrorType};
+
+    // Plays the sender side: each chunk is only released once the receiver
+    // has answered the previous one.
+    struct ScriptedPort {
+        chunks: VecDeque<Vec<u8>>,
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+    }
+
+    impl ScriptedPort {
+        fn new(chunks: Vec<Vec<u8>>) -> Self {
+            Self {
+                chunks: chunks.into(),
+                input: VecDeque::new(),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl ErrorType for ScriptedPort {
+        type Error = ErrorKind;
+    }
+
+    impl Read for ScriptedPort {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let mut n = 0;
+            while n < buf.len() {
+                match self.input.pop_front() {
+                    Some(b) => buf[n] = b,
+                    None => break,
+                }
+                n += 1;
+            }
+            Ok(n)
+        }
+    }
+
+    impl ReadReady for ScriptedPort {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.input.is_empty())
+        }
+    }
+
+    impl Write for ScriptedPort {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.output.extend_from_slice(buf);
+            if let Some(chunk) = self.chunks.pop_front() {
+                self.input.extend(chunk);
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct RamSink {
+        data: Vec<u8>,
+        committed: Option<usize>,
+    }
+
+    impl FirmwareSink for RamSink {
+        type Error = ();
+
+        fn capacity(&self) -> usize {
+            4096
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Self::Error> {
+            assert_eq!(offset, self.data.len());
+            self.data.extend_from_slice(data);
+            Ok(())
+        }
+
+        fn commit(&mut self, len: usize) -> Result<(), Self::Error> {
+            self.committed = Some(len);
+            Ok(())
+        }
+    }
+
+    fn block(num: u8, payload: &[u8], size: usize) -> Vec<u8> {
+        let mut data = payload.to_vec();
+        data.resize(size, 0x1A);
+        let mut out = vec![if size == LONG_BLOCK_SIZE { STX } else { SOH }, num, !num];
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&crc16(0, &data).to_be_bytes());
+        out
+    }
+
+    fn image(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7) as u8).collect()
+    }
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(0, b"123456789"), 0x31C3);
+    }
+
+    #[test]
+    fn test_receive() {
+        let img = image(1100);
+        let chunks = vec![
+            block(1, &img[..1024], LONG_BLOCK_SIZE),
+            block(2, &img[1024..], SHORT_BLOCK_SIZE),
+            // Duplicate of the last block, as if our ACK had been lost.
+            block(2, &img[1024..], SHORT_BLOCK_SIZE),
+            vec![EOT],
+        ];
+
+        let mut port = ScriptedPort::new(chunks);
+        let mut sink = RamSink::default();
+        let info = ImageInfo {
+            len: img.len(),
+            crc: crc16(0, &img),
+        };
+        assert_eq!(
+            receive_with_clock(&mut port, &mut sink, &info, &MockTime::with_step(1)),
+            Ok(img.len())
+        );
+        assert_eq!(sink.data, img);
+        assert_eq!(sink.committed, Some(img.len()));
+        assert_eq!(port.output, vec![CRC_MODE, ACK, ACK, ACK, ACK]);
+    }
+
+    #[test]
+    fn test_receive_progress() {
+        let img = image(1200);
+        let chunks = vec![
+            block(1, &img[..128], SHORT_BLOCK_SIZE),
+            block(2, &img[128..1152], LONG_BLOCK_SIZE),
+            block(3, &img[1152..], SHORT_BLOCK_SIZE),
+            vec![EOT],
+        ];
+
+        let mut port = ScriptedPort::new(chunks);
+        let mut sink = RamSink::default();
+        let info = ImageInfo {
+            len: img.len(),
+            crc: crc16(0, &img),
+        };
+        let mut calls = Vec::new();
+        assert_eq!(
+            receive_with_progress(
+                &mut port,
+                &mut sink,
+                &info,
+                &MockTime::with_step(1),
+                512,
+                |n| calls.push(n)
+            ),
+            Ok(img.len())
+        );
+        assert_eq!(calls, vec![1152, 1200]);
+    }
+
+    #[test]
+    fn test_receive_bad_block_is_nacked() {
+        let img = image(100);
+        let mut bad = block(1, &img, SHORT_BLOCK_SIZE);
+        bad[10] ^= 0xFF;
+        let chunks = vec![bad, block(1, &img, SHORT_BLOCK_SIZE), vec![EOT]];
+
+        let mut port = ScriptedPort::new(chunks);
+        let mut sink = RamSink::default();
+        let info = ImageInfo {
+            len: img.len(),
+            crc: crc16(0, &img),
+        };
+        assert_eq!(
+            receive_with_clock(&mut port, &mut sink, &info, &MockTime::with_step(1)),
+            Ok(img.len())
+        );
+        assert_eq!(port.output, vec![CRC_MODE, NAK, ACK, ACK]);
+        assert_eq!(sink.data, img);
+    }
+
+    #[test]
+    fn test_receive_image_mismatch() {
+        let img = image(100);
+        let chunks = vec![block(1, &img, SHORT_BLOCK_SIZE), vec![EOT]];
+
+        let mut port = ScriptedPort::new(chunks);
+        let mut sink = RamSink::default();
+        let info = ImageInfo {
+            len: img.len(),
+            crc: crc16(0, &img) ^ 1,
+        };
+        assert_eq!(
+            receive_with_clock(&mut port, &mut sink, &info, &MockTime::with_step(1)),
+            Err(XmodemError::ImageMismatch)
+        );
+        assert_eq!(sink.committed, None);
+    }
+
+    #[test]
+    fn test_receive_sequence_error() {
+        let img = image(100);
+        let chunks = vec![block(3, &img, SHORT_BLOCK_SIZE)];
+
+        let mut port = ScriptedPort::new(chunks);
+        let mut sink = RamSink::default();
+        let info = ImageInfo {
+            len: img.len(),
+            crc: crc16(0, &img),
+        };
+        assert_eq!(
+            receive_with_clock(&mut port, &mut sink, &info, &MockTime::with_step(1)),
+            Err(XmodemError::Sequence)
+        );
+        assert!(port.output.ends_with(&[CAN, CAN, CAN]));
+        assert_eq!(sink.committed, None);
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Write throughput measurement for `embedded_io` ports.
+//!
+//! [`measure_write`] sends the same buffer twice, once as a single transfer
+//! and once split into `chunk`-sized transfers, each followed by a flush.
+//! The first gives the rate the port sustains, the difference between the
+//! two the cost of each extra transaction. Both runs are timed with the
+//! finest counter of the clock.
+
+use crate::time::{
+    source::{SysTick, TimeSource},
+    Duration,
+};
+use core::fmt;
+use embedded_io::{Error, ErrorKind, Write};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchReport {
+    pub bytes: usize,
+    pub transfers: usize,
+    /// Time of the single transfer.
+    pub raw: Duration,
+    /// Time of the `transfers` chunked transfers.
+    pub chunked: Duration,
+}
+
+fn rate(bytes: usize, time: Duration) -> u64 {
+    if time.is_zero() {
+        return 0;
+    }
+    (bytes as u128 * 1_000_000 / time.as_micros() as u128) as u64
+}
+
+impl BenchReport {
+    /// Bytes per second of the single transfer.
+    pub fn raw_rate(&self) -> u64 {
+        rate(self.bytes, self.raw)
+    }
+
+    /// Bytes per second including the per-transfer overhead.
+    pub fn effective_rate(&self) -> u64 {
+        rate(self.bytes, self.chunked)
+    }
+
+    pub fn overhead_per_transfer(&self) -> Duration {
+        Duration::from_micros(
+            self.chunked.saturating_sub(self.raw).as_micros() / self.transfers.max(1) as u64,
+        )
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes: raw {} B/s, effective {} B/s over {} transfers, {} us overhead each",
+            self.bytes,
+            self.raw_rate(),
+            self.effective_rate(),
+            self.transfers,
+            self.overhead_per_transfer().as_micros()
+        )
+    }
+}
+
+fn elapsed<C: TimeSource + ?Sized>(clock: &C, start: u64) -> Duration {
+    let counts = clock.counter().wrapping_sub(start) as u128;
+    let micros = counts * 1_000_000 / clock.counter_hz() as u128;
+    Duration::from_micros(micros.min(u64::MAX as u128) as u64)
+}
+
+/// Measures writing `buf` to `port`, see the module documentation.
+pub fn measure_write<P: Write>(
+    port: &mut P,
+    buf: &[u8],
+    chunk: usize,
+) -> Result<BenchReport, ErrorKind> {
+    measure_write_with_clock(port, buf, chunk, &SysTick)
+}
+
+pub fn measure_write_with_clock<P, C>(
+    port: &mut P,
+    buf: &[u8],
+    chunk: usize,
+    clock: &C,
+) -> Result<BenchReport, ErrorKind>
+where
+    P: Write,
+    C: TimeSource + ?Sized,
+{
+    if chunk == 0 {
+        return Err(ErrorKind::InvalidInput);
+    }
+
+    let start = clock.counter();
+    port.write_all(buf).map_err(|e| e.kind())?;
+    port.flush().map_err(|e| e.kind())?;
+    let raw = elapsed(clock, start);
+
+    let start = clock.counter();
+    for part in buf.chunks(chunk) {
+        port.write_all(part).map_err(|e| e.kind())?;
+        port.flush().map_err(|e| e.kind())?;
+    }
+    let chunked = elapsed(clock, start);
+
+    Ok(BenchReport {
+        bytes: buf.len(),
+        transfers: buf.len().div_ceil(chunk),
+        raw,
+        chunked,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::source::MockTime;
+    use blueos_test_macro::test;
+    use embedded_io::ErrorType;
+
+    // Takes one tick per byte and one per flush.
+    struct TimedPort<'a>(&'a MockTime);
+
+    impl ErrorType for TimedPort<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl Write for TimedPort<'_> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.0.advance(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.0.advance(1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_measure_write() {
+        let clock = MockTime::new();
+        let buf = [0x55u8; 64];
+        let report = measure_write_with_clock(&mut TimedPort(&clock), &buf, 16, &clock).unwrap();
+        assert_eq!(report.transfers, 4);
+        assert_eq!(report.raw, Duration::from_ticks(65));
+        assert_eq!(report.chunked, Duration::from_ticks(68));
+        assert_eq!(
+            report.overhead_per_transfer().as_micros(),
+            Duration::from_ticks(3).as_micros() / 4
+        );
+        assert!(report.raw_rate() > report.effective_rate());
+    }
+
+    #[test]
+    fn test_measure_write_zero_chunk() {
+        let clock = MockTime::new();
+        assert_eq!(
+            measure_write_with_clock(&mut TimedPort(&clock), &[0; 4], 0, &clock).unwrap_err(),
+            ErrorKind::InvalidInput
+        );
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Write-command/read-response exchanges.
+//!
+//! Many peripherals answer a command with a response of known length,
+//! sometimes only after a processing delay. [`command_response`] does the
+//! whole exchange over any `embedded_io` port, so drivers don't each open-code
+//! the write, the wait and the bounded read.
+
+use crate::{
+    scheduler,
+    time::{
+        source::{SysTick, TimeSource},
+        Deadline, Duration,
+    },
+};
+use embedded_io::{Error, ErrorKind, Read, ReadReady, Write};
+
+/// Writes `cmd`, waits `delay` and reads exactly `resp.len()` bytes.
+///
+/// The delay suspends the calling thread, so other threads run while the
+/// device processes the command. `timeout` bounds the read of the whole
+/// response and is measured from the end of the delay. While no data
+/// arrives the thread sleeps a tick between polls. Port errors are
+/// returned as their [`ErrorKind`], a missing response as
+/// [`ErrorKind::TimedOut`].
+pub fn command_response<P>(
+    port: &mut P,
+    cmd: &[u8],
+    resp: &mut [u8],
+    delay: Duration,
+    timeout: Duration,
+) -> Result<(), ErrorKind>
+where
+    P: Read + ReadReady + Write,
+{
+    command_response_with_clock(port, cmd, resp, delay, timeout, &SysTick)
+}
+
+/// [`command_response`] with the response timeout measured on `clock`.
+pub fn command_response_with_clock<P, C>(
+    port: &mut P,
+    cmd: &[u8],
+    resp: &mut [u8],
+    delay: Duration,
+    timeout: Duration,
+    clock: &C,
+) -> Result<(), ErrorKind>
+where
+    P: Read + ReadReady + Write,
+    C: TimeSource + ?Sized,
+{
+    port.write_all(cmd).map_err(|e| e.kind())?;
+    port.flush().map_err(|e| e.kind())?;
+    if !delay.is_zero() {
+        scheduler::suspend_me_for(delay.as_ticks());
+    }
+
+    let deadline = Deadline::new(clock, timeout);
+    let mut n = 0;
+    while n < resp.len() {
+        if port.read_ready().map_err(|e| e.kind())? {
+            let read = port.read(&mut resp[n..]).map_err(|e| e.kind())?;
+            n += read;
+            if read > 0 {
+                continue;
+            }
+        }
+        // A port that claims to be ready but hands out nothing doesn't
+        // count as progress either.
+        if deadline.is_expired() {
+            return Err(ErrorKind::TimedOut);
+        }
+        scheduler::suspend_me_for(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::source::MockTime;
+    use alloc::{collections::VecDeque, vec::Vec};
+    use blueos_test_macro::test;
+    use embedded_io::ErrorType;
+
+    // Answers every write with `reply`, handing it out one byte per read.
+    struct EchoDevice {
+        reply: Vec<u8>,
+        input: VecDeque<u8>,
+        written: Vec<u8>,
+    }
+
+    impl EchoDevice {
+        fn new(reply: &[u8]) -> Self {
+            Self {
+                reply: reply.to_vec(),
+                input: VecDeque::new(),
+                written: Vec::new(),
+            }
+        }
+    }
+
+    impl ErrorType for EchoDevice {
+        type Error = ErrorKind;
+    }
+
+    impl Read for EchoDevice {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            match self.input.pop_front() {
+                Some(b) if !buf.is_empty() => {
+                    buf[0] = b;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    impl ReadReady for EchoDevice {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.input.is_empty())
+        }
+    }
+
+    impl Write for EchoDevice {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.written.extend_from_slice(buf);
+            self.input.extend(self.reply.iter().copied());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    // Reports data ready but never returns any.
+    struct StalledDevice;
+
+    impl ErrorType for StalledDevice {
+        type Error = ErrorKind;
+    }
+
+    impl Read for StalledDevice {
+        fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+            Ok(0)
+        }
+    }
+
+    impl ReadReady for StalledDevice {
+        fn read_ready(&mut self) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    impl Write for StalledDevice {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_command_response() {
+        let mut dev = EchoDevice::new(&[0xBE, 0xEF, 0x92]);
+        let mut resp = [0u8; 3];
+        let clock = MockTime::new();
+        command_response_with_clock(
+            &mut dev,
+            &[0x24, 0x00],
+            &mut resp,
+            Duration::ZERO,
+            Duration::from_millis(10),
+            &clock,
+        )
+        .unwrap();
+        assert_eq!(dev.written, [0x24, 0x00]);
+        assert_eq!(resp, [0xBE, 0xEF, 0x92]);
+    }
+
+    #[test]
+    fn test_command_response_timeout() {
+        let mut dev = EchoDevice::new(&[0xBE]);
+        let mut resp = [0u8; 3];
+        let clock = MockTime::with_step(1);
+        assert_eq!(
+            command_response_with_clock(
+                &mut dev,
+                &[0x24, 0x00],
+                &mut resp,
+                Duration::ZERO,
+                Duration::from_millis(10),
+                &clock,
+            ),
+            Err(ErrorKind::TimedOut)
+        );
+        assert_eq!(resp[0], 0xBE);
+    }
+
+    #[test]
+    fn test_command_response_stalled() {
+        let mut resp = [0u8; 2];
+        let clock = MockTime::with_step(1);
+        assert_eq!(
+            command_response_with_clock(
+                &mut StalledDevice,
+                &[0x24],
+                &mut resp,
+                Duration::ZERO,
+                Duration::from_millis(10),
+                &clock,
+            ),
+            Err(ErrorKind::TimedOut)
+        );
+    }
+}
@@ -23,6 +23,7 @@ use crate::{
     sync::SpinLock,
 };
 use alloc::sync::Arc;
+use blueos_driver::uart::InterruptType;
 use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
 use spin::Once;
 pub(crate) struct DumbUart;
@@ -96,6 +97,12 @@ impl UartOps for DumbUart {
     fn clear_rx_interrupt(&mut self) {}
 
     fn clear_tx_interrupt(&mut self) {}
+
+    fn get_interrupt(&mut self) -> InterruptType {
+        InterruptType::Unknown
+    }
+
+    fn clear_interrupt(&mut self, _intr: InterruptType) {}
 }
 
 pub(crate) fn get_early_uart<'a>() -> &'a SpinLock<dyn UartOps> {
@@ -21,8 +21,10 @@ use core::{
 use embedded_io::ErrorKind;
 use libc::*;
 use spin::{Once, RwLock as SpinRwLock};
+pub mod bench;
 #[cfg(virtio)]
 pub mod block;
+pub mod command;
 pub mod console;
 mod error;
 #[cfg(enable_net)]
@@ -53,6 +55,7 @@ pub enum DeviceRequest {
     Suspend = 0x02,      // suspend device
     Config = 0x03,       // configure device
     Close = 0x04,        // close device
+    GetStatus = 0x05,    // read device status
     NotSupported = 0x00, // not supported
 }
 
@@ -63,6 +66,7 @@ impl From<u32> for DeviceRequest {
             0x02 => Self::Suspend,
             0x03 => Self::Config,
             0x04 => Self::Close,
+            0x05 => Self::GetStatus,
             _ => Self::NotSupported,
         }
     }
@@ -136,7 +136,11 @@ impl Device for Tty {
         // normal character
         loop {
             let mut temp_buf = [0u8; 512];
-            let nbytes = self.serial.read(_pos, &mut temp_buf, is_blocking).unwrap();
+            let nbytes = match self.serial.recv(&mut temp_buf, is_blocking) {
+                Ok(nbytes) => nbytes,
+                Err(e) if e.is_ignored(self.serial.termios.iflag) => continue,
+                Err(e) => return Err(e.into()),
+            };
             let mut i = 0;
             while i < nbytes {
                 let ch = temp_buf[i];
@@ -12,16 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod slip;
 pub mod uart;
 use crate::{
-    devices::{tty::termios::Termios, Device, DeviceBase, DeviceClass, DeviceId, DeviceRequest},
-    irq,
-    sync::{
-        atomic_wait::{atomic_wait, atomic_wake},
-        spinlock::SpinLock,
+    devices::{
+        tty::termios::{Iflags, Termios},
+        Device, DeviceBase, DeviceClass, DeviceId, DeviceRequest,
     },
+    sync::{atomic_wait::atomic_wake, spinlock::SpinLock},
 };
+#[cfg(not(serial_polling))]
+use crate::{irq, sync::atomic_wait::atomic_wait};
 use alloc::{format, string::String, sync::Arc};
+use blueos_driver::uart::{InterruptType, RxErrors};
 use blueos_infra::ringbuffer::BoxedRingBuffer;
 use blueos_kconfig::{SERIAL_RX_FIFO_SIZE, SERIAL_TX_FIFO_SIZE};
 use core::sync::atomic::AtomicUsize;
@@ -49,6 +52,24 @@ pub enum SerialError {
     InvalidParameter,
     #[error("Operation timed out")]
     TimedOut,
+    #[error("Frame too long")]
+    FrameTooLong,
+    #[error("Invalid frame")]
+    InvalidFrame,
+}
+
+impl SerialError {
+    /// Whether `iflag` says to drop the character lost to this error:
+    /// IGNPAR for parity and framing errors, IGNBRK for breaks, and both
+    /// for overruns.
+    pub fn is_ignored(&self, iflag: Iflags) -> bool {
+        match self {
+            Self::Parity | Self::Framing => iflag.contains(Iflags::IGNPAR),
+            Self::Break => iflag.contains(Iflags::IGNBRK),
+            Self::Overrun => iflag.contains(Iflags::IGNPAR | Iflags::IGNBRK),
+            _ => false,
+        }
+    }
 }
 
 impl embedded_io::Error for SerialError {
@@ -59,6 +80,7 @@ impl embedded_io::Error for SerialError {
             Self::BufferEmpty | Self::InvalidParameter => ErrorKind::InvalidInput,
             Self::DeviceError => ErrorKind::Other,
             Self::TimedOut => ErrorKind::TimedOut,
+            Self::FrameTooLong | Self::InvalidFrame => ErrorKind::InvalidData,
         }
     }
 }
@@ -71,10 +93,40 @@ impl From<SerialError> for ErrorKind {
             SerialError::BufferEmpty | SerialError::InvalidParameter => ErrorKind::InvalidInput,
             SerialError::DeviceError => ErrorKind::Other,
             SerialError::TimedOut => ErrorKind::TimedOut,
+            SerialError::FrameTooLong | SerialError::InvalidFrame => ErrorKind::InvalidData,
         }
     }
 }
 
+/// Latches a receive line error in `errors`. Returns false if `error` is
+/// not a line error.
+fn latch_rx_error(errors: &mut RxErrors, error: &SerialError) -> bool {
+    let flag = match error {
+        SerialError::Overrun => &mut errors.overrun,
+        SerialError::Break => &mut errors.break_,
+        SerialError::Parity => &mut errors.parity,
+        SerialError::Framing => &mut errors.framing,
+        _ => return false,
+    };
+    *flag = true;
+    true
+}
+
+/// Takes one latched line error out of `errors`, overruns first.
+fn take_rx_error(errors: &mut RxErrors) -> Option<SerialError> {
+    for (flag, error) in [
+        (&mut errors.overrun, SerialError::Overrun),
+        (&mut errors.break_, SerialError::Break),
+        (&mut errors.parity, SerialError::Parity),
+        (&mut errors.framing, SerialError::Framing),
+    ] {
+        if core::mem::take(flag) {
+            return Some(error);
+        }
+    }
+    None
+}
+
 // TODO: add DMA support
 pub trait UartOps:
     Read
@@ -95,6 +147,8 @@ pub trait UartOps:
     fn set_tx_interrupt(&mut self, enable: bool);
     fn clear_rx_interrupt(&mut self);
     fn clear_tx_interrupt(&mut self);
+    fn get_interrupt(&mut self) -> InterruptType;
+    fn clear_interrupt(&mut self, intr: InterruptType);
 }
 
 #[derive(Debug)]
@@ -133,6 +187,9 @@ pub struct Serial {
     pub termios: Termios,
     rx_fifo: SerialRxFifo,
     tx_fifo: SerialTxFifo,
+    // Line errors seen by `recvchars`, reported by the next read that finds
+    // no data.
+    rx_errors: SpinLock<RxErrors>,
     pub uart_ops: Arc<SpinLock<dyn UartOps>>,
 }
 
@@ -144,6 +201,7 @@ impl Serial {
             termios,
             rx_fifo: SerialRxFifo::new(SERIAL_RX_FIFO_SIZE.max(SERIAL_RX_FIFO_MIN_SIZE)),
             tx_fifo: SerialTxFifo::new(SERIAL_TX_FIFO_SIZE.max(SERIAL_TX_FIFO_MIN_SIZE)),
+            rx_errors: SpinLock::new(RxErrors::default()),
             uart_ops,
         }
     }
@@ -156,6 +214,20 @@ impl Serial {
         }
     }
 
+    /// Stops the interrupt handler from draining the receive FIFO, leaving
+    /// incoming bytes to whoever polls `uart_ops` directly.
+    pub fn suspend_rx(&self) {
+        self.uart_ops.irqsave_lock().set_rx_interrupt(false);
+    }
+
+    /// Undoes [`Serial::suspend_rx`]. Reception is only re-armed if the
+    /// device is open.
+    pub fn resume_rx(&self) {
+        if cfg!(not(serial_polling)) && self.is_opened() {
+            self.uart_ops.irqsave_lock().set_rx_interrupt(true);
+        }
+    }
+
     fn rx_disable(&self) -> Result<(), SerialError> {
         let _ = atomic_wake(&self.rx_fifo.futex, 1);
         self.uart_ops.irqsave_lock().set_rx_interrupt(false);
@@ -170,6 +242,48 @@ impl Serial {
         Ok(())
     }
 
+    /// Reads straight from the UART, for `serial_polling`.
+    #[cfg(serial_polling)]
+    fn poll_rx(&self, buf: &mut [u8], is_nonblocking: bool) -> Result<usize, SerialError> {
+        loop {
+            {
+                let mut uart_ops = self.uart_ops.irqsave_lock();
+                if uart_ops.read_ready()? {
+                    return uart_ops.read(buf);
+                }
+            }
+            if is_nonblocking {
+                return Ok(0);
+            }
+            crate::scheduler::yield_me();
+        }
+    }
+
+    /// Writes straight to the UART, for `serial_polling`. A non-blocking
+    /// write only returns early if the UART can't take the first byte.
+    #[cfg(serial_polling)]
+    fn poll_tx(&self, buf: &[u8], is_nonblocking: bool) -> Result<usize, SerialError> {
+        let mut uart_ops = self.uart_ops.irqsave_lock();
+        if is_nonblocking {
+            if !uart_ops.write_ready()? {
+                return Ok(0);
+            }
+            return uart_ops.write(buf);
+        }
+        uart_ops.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    /// Reads like [`Device::read`], but keeps the [`SerialError`], so that a
+    /// line discipline can tell the line errors apart.
+    pub fn recv(&self, buf: &mut [u8], is_nonblocking: bool) -> Result<usize, SerialError> {
+        #[cfg(serial_polling)]
+        return self.poll_rx(buf, is_nonblocking);
+        #[cfg(not(serial_polling))]
+        self.fifo_rx(buf, is_nonblocking)
+    }
+
+    #[cfg(not(serial_polling))]
     fn fifo_rx(&self, buf: &mut [u8], is_nonblocking: bool) -> Result<usize, SerialError> {
         let len = buf.len();
         let mut count = 0;
@@ -186,23 +300,29 @@ impl Serial {
                 n += slice_len;
             }
             reader.pop_done(n);
+            if n > 0 {
+                break;
+            }
 
-            if !is_nonblocking {
-                // if the available data is less than the requested data, wait for data
-                if n == 0 {
-                    atomic_wait(&self.rx_fifo.futex, 0, None).map_err(|_| SerialError::TimedOut)?;
-                } else {
-                    break;
-                }
-            } else {
+            // A latched line error is reported once the buffered bytes have
+            // been read.
+            if let Some(e) = take_rx_error(&mut self.rx_errors.irqsave_lock()) {
+                return Err(e);
+            }
+            if is_nonblocking {
                 break;
             }
+            atomic_wait(&self.rx_fifo.futex, 0, None).map_err(|_| SerialError::TimedOut)?;
         }
 
         Ok(count)
     }
 
+    #[cfg(not(serial_polling))]
     fn fifo_tx(&self, buf: &[u8], is_nonblocking: bool) -> Result<usize, SerialError> {
+        if irq::is_in_irq() {
+            return self.irq_tx(buf);
+        }
         let len = buf.len();
         let mut count = 0;
         let mut writer = unsafe { self.tx_fifo.rb.writer() };
@@ -224,27 +344,46 @@ impl Serial {
                 writer.push_done(n);
                 self.uart_ops.irqsave_lock().set_tx_interrupt(true);
                 // write some data to uart to trigger interrupt
-                if !irq::is_in_irq() {
-                    let _ = self.xmitchars();
-                }
+                let _ = self.xmitchars();
             }
 
-            if !is_nonblocking && !irq::is_in_irq() {
-                if !writer.is_empty() {
-                    // wait for data to be written
-                    atomic_wait(&self.tx_fifo.futex, 0, None).map_err(|_| SerialError::TimedOut)?;
-                    self.uart_ops.irqsave_lock().set_tx_interrupt(false);
-                } else if count >= len {
-                    break;
-                }
-            } else {
+            // Queued data is sent by the TX interrupt, only a full queue
+            // holds a blocking write up.
+            if count >= len || is_nonblocking {
+                break;
+            }
+            if cfg!(serial_tx_full_drop) {
+                count = len;
                 break;
             }
+            atomic_wait(&self.tx_fifo.futex, 0, None).map_err(|_| SerialError::TimedOut)?;
         }
 
         Ok(count)
     }
 
+    /// Writes from interrupt context, which can't wait for the TX interrupt.
+    /// The queued data goes out first to keep the output in order, then
+    /// `buf` is written straight to the UART.
+    #[cfg(not(serial_polling))]
+    fn irq_tx(&self, buf: &[u8]) -> Result<usize, SerialError> {
+        {
+            let mut uart_ops = self.uart_ops.irqsave_lock();
+            // Safety: the tx_fifo reader is only used with uart_ops locked
+            let mut reader = unsafe { self.tx_fifo.rb.reader() };
+            while !reader.is_empty() {
+                let slice = reader.pop_slice();
+                uart_ops.write_all(slice)?;
+                let n = slice.len();
+                reader.pop_done(n);
+            }
+            uart_ops.set_tx_interrupt(false);
+            uart_ops.write_all(buf)?;
+        }
+        let _ = atomic_wake(&self.tx_fifo.futex, 1);
+        Ok(buf.len())
+    }
+
     /// this Function is called from the UART interrupt handler
     /// when an interrupt is received indicating that there is more space in the
     /// transmit FIFO
@@ -252,7 +391,7 @@ impl Serial {
         let mut nbytes: usize = 0;
         {
             let mut uart_ops = self.uart_ops.irqsave_lock();
-            // Safety: tx_fifo reader is only accessed in the UART interrupt handler
+            // Safety: the tx_fifo reader is only used with uart_ops locked
             let mut reader = unsafe { self.tx_fifo.rb.reader() };
             while !reader.is_empty() && uart_ops.write_ready()? {
                 let buf = reader.pop_slice();
@@ -280,8 +419,12 @@ impl Serial {
     /// this Function is called from the UART interrupt handler
     /// when an interrupt is received indicating that there is more data in the
     /// receive FIFO
+    ///
+    /// Line errors are latched for the reader, which gets them once the
+    /// buffered data has been read. The last one is also returned.
     pub fn recvchars(&self) -> Result<usize, SerialError> {
         let mut nbytes: usize = 0;
+        let mut line_error = None;
         {
             let mut uart_ops = self.uart_ops.irqsave_lock();
             // Safety: rx_fifo writer is only accessed in the UART interrupt handler
@@ -293,17 +436,25 @@ impl Serial {
                         nbytes += n;
                         writer.push_done(n);
                     }
+                    // The bad character has left the FIFO, keep receiving
+                    // what follows it.
+                    Err(e) if latch_rx_error(&mut self.rx_errors.irqsave_lock(), &e) => {
+                        line_error = Some(e)
+                    }
                     Err(e) => return Err(e),
                 }
             }
         }
 
         // TODO: add notify for poll/select
-        if nbytes > 0 {
+        if nbytes > 0 || line_error.is_some() {
             let _ = atomic_wake(&self.rx_fifo.futex, 1);
         }
 
-        Ok(nbytes)
+        match line_error {
+            Some(e) => Err(e),
+            None => Ok(nbytes),
+        }
     }
 }
 
@@ -324,6 +475,7 @@ impl Device for Serial {
         if !self.is_opened() {
             let mut uart_ops = self.uart_ops.irqsave_lock();
             uart_ops.setup(&self.termios)?;
+            #[cfg(not(serial_polling))]
             uart_ops.set_rx_interrupt(true);
         }
 
@@ -349,10 +501,13 @@ impl Device for Serial {
     }
 
     fn read(&self, _pos: u64, buf: &mut [u8], is_nonblocking: bool) -> Result<usize, ErrorKind> {
-        self.fifo_rx(buf, is_nonblocking).map_err(|e| e.into())
+        self.recv(buf, is_nonblocking).map_err(|e| e.into())
     }
 
     fn write(&self, _pos: u64, buf: &[u8], is_nonblocking: bool) -> Result<usize, ErrorKind> {
+        #[cfg(serial_polling)]
+        return self.poll_tx(buf, is_nonblocking).map_err(|e| e.into());
+        #[cfg(not(serial_polling))]
         self.fifo_tx(buf, is_nonblocking).map_err(|e| e.into())
     }
 
@@ -360,4 +515,19 @@ impl Device for Serial {
         let mut uart_ops = self.uart_ops.irqsave_lock();
         uart_ops.ioctl(request, arg).map_err(|e| e.into())
     }
+
+    /// Waits until the queued data and the UART FIFO have been sent.
+    fn sync(&self) -> Result<(), ErrorKind> {
+        #[cfg(not(serial_polling))]
+        if irq::is_in_irq() {
+            self.irq_tx(&[])?;
+        } else {
+            while !self.tx_fifo.rb.is_empty() {
+                self.xmitchars()?;
+                crate::scheduler::yield_me();
+            }
+        }
+        self.uart_ops.irqsave_lock().flush()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,79 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{uart::UartDevice, SerialError, UartOps};
+use blueos_hal::PlatPeri;
+use blueos_infra::slip::{self, Decoder};
+use embedded_io::Write;
+
+/// Packet oriented view of a UART, framed with SLIP.
+///
+/// Both directions poll the UART directly, so it must not be used on a port
+/// that is concurrently driven by a [`super::Serial`] interrupt handler.
+pub struct SlipUart<T: PlatPeri> {
+    uart: UartDevice<T>,
+}
+
+impl<T> SlipUart<T>
+where
+    T: blueos_hal::uart::Uart<
+        blueos_driver::uart::UartConfig,
+        (),
+        blueos_driver::uart::InterruptType,
+        blueos_driver::uart::UartCtrlStatus,
+    >,
+{
+    pub fn new(uart: UartDevice<T>) -> Self {
+        Self { uart }
+    }
+
+    pub fn into_inner(self) -> UartDevice<T> {
+        self.uart
+    }
+
+    /// Sends `packet` as one SLIP frame and waits until it has left the wire.
+    pub fn write_packet(&mut self, packet: &[u8]) -> Result<(), SerialError> {
+        self.uart.write_all(&[slip::END])?;
+        for &byte in packet {
+            match slip::escape(byte) {
+                Some(escaped) => self.uart.write_all(&[slip::ESC, escaped])?,
+                None => self.uart.write_all(&[byte])?,
+            }
+        }
+        self.uart.write_all(&[slip::END])?;
+        self.uart.flush()
+    }
+
+    /// Blocks until a complete frame is received and returns its length.
+    ///
+    /// A frame that does not fit in `buf` is dropped and reported as
+    /// [`SerialError::FrameTooLong`] once its end is seen, a malformed escape
+    /// as [`SerialError::InvalidFrame`].
+    pub fn read_packet(&mut self, buf: &mut [u8]) -> Result<usize, SerialError> {
+        let mut decoder = Decoder::new();
+        loop {
+            let byte = match self.uart.read_byte() {
+                Ok(byte) => byte,
+                Err(SerialError::BufferEmpty) => continue,
+                Err(e) => return Err(e),
+            };
+            match decoder.feed(byte, buf) {
+                Ok(Some(len)) => return Ok(len),
+                Ok(None) => {}
+                Err(slip::DecodeError::Overflow) => return Err(SerialError::FrameTooLong),
+                Err(slip::DecodeError::BadEscape) => return Err(SerialError::InvalidFrame),
+            }
+        }
+    }
+}
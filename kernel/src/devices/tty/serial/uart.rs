@@ -19,7 +19,7 @@ use crate::devices::{
     },
     DeviceRequest,
 };
-use blueos_driver::uart::{InterruptType, UartCtrlStatus};
+use blueos_driver::uart::{InterruptType, RxErrors, UartCtrlStatus};
 use blueos_hal::{
     uart::{Uart, UartWithReset},
     HasInterruptReg, PlatPeri,
@@ -28,6 +28,11 @@ use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
 
 pub struct UartDevice<T: PlatPeri> {
     uart: &'static T,
+    // Serial device number, the context of the interrupt handler.
+    index: usize,
+    // Line errors hit after a read had already got some bytes, reported by
+    // the next read.
+    rx_errors: RxErrors,
 }
 
 unsafe impl<T> Send for UartDevice<T> where
@@ -71,15 +76,21 @@ where
         blueos_driver::uart::UartCtrlStatus,
     >,
 {
-    pub fn new(uart: &'static T) -> Self {
-        UartDevice { uart }
+    pub fn new(uart: &'static T, index: usize) -> Self {
+        UartDevice {
+            uart,
+            index,
+            rx_errors: RxErrors::default(),
+        }
     }
 }
 
 impl From<blueos_hal::err::HalError> for super::SerialError {
     fn from(value: blueos_hal::err::HalError) -> Self {
         match value {
-            blueos_hal::err::HalError::InvalidParam => super::SerialError::InvalidParameter,
+            blueos_hal::err::HalError::InvalidParam | blueos_hal::err::HalError::NotSupport => {
+                super::SerialError::InvalidParameter
+            }
             blueos_hal::err::HalError::Timeout => super::SerialError::TimedOut,
             blueos_hal::err::HalError::Other(s) => match s {
                 "Overrun Error" => super::SerialError::Overrun,
@@ -103,12 +114,14 @@ where
     >,
 {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // Only wait for room for the first byte and return once the FIFO is
+        // full, so that the TX interrupt never spins on a long buffer.
+        while self.uart.is_tx_fifo_full() {}
         let mut count = 0;
-        // write until the buffer is full
-        while count < buf.len() {
-            if self.uart.is_tx_fifo_full() {
-                continue;
-            }
+        while count < buf.len() && !self.uart.is_tx_fifo_full() {
             self.uart.write_data8(buf[count]);
             count += 1;
         }
@@ -116,6 +129,9 @@ where
     }
 
     fn flush(&mut self) -> Result<(), Self::Error> {
+        // Not every controller's busy flag covers the FIFO, so drain the
+        // FIFO first and then wait for the last frame to leave the line.
+        while !self.uart.is_tx_fifo_empty() {}
         while self.uart.is_bus_busy() {}
         Ok(())
     }
@@ -148,6 +164,9 @@ where
         if buf.is_empty() {
             return Ok(0);
         }
+        if let Some(e) = super::take_rx_error(&mut self.rx_errors) {
+            return Err(e);
+        }
 
         let mut count = 0;
 
@@ -158,6 +177,12 @@ where
                     count += 1;
                 }
                 Err(super::SerialError::BufferEmpty) => break,
+                // Hand out the good bytes first. The bad character is gone
+                // from the FIFO, so a line error is kept for the next read.
+                Err(e) if count > 0 => {
+                    super::latch_rx_error(&mut self.rx_errors, &e);
+                    break;
+                }
                 Err(e) => return Err(e),
             }
         }
@@ -193,17 +218,23 @@ where
         &mut self,
         termios: &crate::devices::tty::termios::Termios,
     ) -> Result<(), super::SerialError> {
+        let data_bits = if termios.cflag.contains(Cflags::CSIZE_8) {
+            blueos_driver::uart::DataBits::DataBits8
+        } else if termios.cflag.contains(Cflags::CSIZE_7) {
+            blueos_driver::uart::DataBits::DataBits7
+        } else if termios.cflag.contains(Cflags::CSIZE_6) {
+            blueos_driver::uart::DataBits::DataBits6
+        } else {
+            blueos_driver::uart::DataBits::DataBits5
+        };
+        let stop_bits = if termios.cflag.contains(Cflags::CSTOPB) {
+            blueos_driver::uart::StopBits::Two
+        } else {
+            blueos_driver::uart::StopBits::One
+        };
         let config = blueos_driver::uart::UartConfig {
             baudrate: termios.getospeed(),
-            data_bits: if termios.cflag.contains(Cflags::CSIZE_8) {
-                blueos_driver::uart::DataBits::DataBits8
-            } else if termios.cflag.contains(Cflags::CSIZE_7) {
-                blueos_driver::uart::DataBits::DataBits7
-            } else if termios.cflag.contains(Cflags::CSIZE_6) {
-                blueos_driver::uart::DataBits::DataBits6
-            } else {
-                blueos_driver::uart::DataBits::DataBits5
-            },
+            data_bits,
             parity: if !termios.cflag.contains(Cflags::PARENB) {
                 blueos_driver::uart::Parity::None
             } else if termios.cflag.contains(Cflags::PARODD) {
@@ -211,16 +242,17 @@ where
             } else {
                 blueos_driver::uart::Parity::Even
             },
-            stop_bits: if termios.cflag.contains(Cflags::CSTOPB) {
-                blueos_driver::uart::StopBits::DataBits2
+            stop_bits,
+            flow_ctrl: if termios.cflag.contains(Cflags::CRTSCTS) {
+                blueos_driver::uart::FlowCtrl::RtsCts
             } else {
-                blueos_driver::uart::StopBits::DataBits1
+                blueos_driver::uart::FlowCtrl::None
             },
-            flow_ctrl: blueos_driver::uart::FlowCtrl::None,
         };
 
         self.uart.clear_interrupt(InterruptType::All);
-        self.uart.set_interrupt_handler(&uart_handler);
+        #[cfg(not(serial_polling))]
+        self.uart.set_interrupt_handler(uart_handler, self.index);
         self.uart.configure(&config)?;
         self.uart.enable();
 
@@ -256,12 +288,18 @@ where
     }
 
     fn set_rx_interrupt(&mut self, enable: bool) {
-        if enable {
-            self.uart
-                .enable_interrupt(blueos_driver::uart::InterruptType::Rx);
-        } else {
-            self.uart
-                .disable_interrupt(blueos_driver::uart::InterruptType::Rx);
+        // The timeout picks up bytes that stay below the FIFO trigger level,
+        // and the errors would otherwise go unnoticed until the next read.
+        for intr in [
+            InterruptType::Rx,
+            InterruptType::RxTimeout,
+            InterruptType::Error,
+        ] {
+            if enable {
+                self.uart.enable_interrupt(intr);
+            } else {
+                self.uart.disable_interrupt(intr);
+            }
         }
     }
 
@@ -285,6 +323,14 @@ where
             .clear_interrupt(blueos_driver::uart::InterruptType::Tx);
     }
 
+    fn get_interrupt(&mut self) -> InterruptType {
+        self.uart.get_interrupt()
+    }
+
+    fn clear_interrupt(&mut self, intr: InterruptType) {
+        self.uart.clear_interrupt(intr);
+    }
+
     fn ioctl(&mut self, request: u32, arg: usize) -> Result<(), super::SerialError> {
         match DeviceRequest::from(request) {
             DeviceRequest::Config => {
@@ -295,29 +341,269 @@ where
             DeviceRequest::Close => {
                 self.uart.disable();
             }
+            // `arg` points at a `UartCtrlStatus`.
+            DeviceRequest::GetStatus => {
+                let status = self.uart.get_ctrl_status()?;
+                unsafe { *(arg as *mut UartCtrlStatus) = status };
+            }
             _ => return Err(super::SerialError::InvalidParameter),
         }
         Ok(())
     }
 }
 
-pub fn uart_handler() {
-    let uart = crate::boards::get_device!(console_uart);
-    let intr = uart.get_interrupt();
+/// Interrupt handler of a serial UART, `index` being the serial device
+/// number passed as the handler context.
+///
+/// It only goes through the `UartOps` of the registered serial device, so any
+/// UART driver can back the console.
+pub fn uart_handler(index: usize) {
+    let serial = crate::boot::get_serial(index as u32);
+    let intr = serial.uart_ops.irqsave_lock().get_interrupt();
     match intr {
-        blueos_driver::uart::InterruptType::Rx => {
-            let t_uart = crate::boot::get_serial(0);
-            if let Err(e) = t_uart.recvchars() {
+        blueos_driver::uart::InterruptType::Rx
+        | blueos_driver::uart::InterruptType::RxTimeout
+        | blueos_driver::uart::InterruptType::Error => {
+            // Line errors also reach the reader, see `Serial::recvchars`.
+            if let Err(e) = serial.recvchars() {
                 log::warn!("uart recvchars error: {:?}", e);
             }
         }
         blueos_driver::uart::InterruptType::Tx => {
-            let t_uart = crate::boot::get_serial(0);
-            if let Err(e) = t_uart.xmitchars() {
+            if let Err(e) = serial.xmitchars() {
                 log::warn!("uart xmitchars error: {:?}", e);
             }
         }
         _ => {}
     }
-    uart.clear_interrupt(intr);
+    serial.uart_ops.irqsave_lock().clear_interrupt(intr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::tty::serial::SerialError;
+    use alloc::{boxed::Box, collections::VecDeque};
+    use blueos_driver::uart::{
+        arm_pl011::{ArmPl011, ArmPl011Config, LineControlRegister},
+        DataBits, Parity, StopBits, UartConfig,
+    };
+    use blueos_hal::{Configuration, Has8bitDataReg, HasFifo, HasLineStatusReg, PlatPeri};
+    use blueos_test_macro::test;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    const MOCK_FIFO_DEPTH: usize = 16;
+
+    // Every status poll moves the transmitter one step: the frame in the
+    // shift register leaves the line, or the next byte is pulled out of the
+    // FIFO. The busy flag only reflects the shift register.
+    // The receiver hands out `rx` in order, `None` is a parity error.
+    #[derive(Default)]
+    struct MockUart {
+        tx_queued: AtomicUsize,
+        shifting: AtomicBool,
+        rx: spin::Mutex<VecDeque<Option<u8>>>,
+    }
+
+    impl MockUart {
+        fn leak() -> &'static Self {
+            Box::leak(Box::default())
+        }
+
+        fn tick(&self) {
+            if self.shifting.load(Ordering::Relaxed) {
+                self.shifting.store(false, Ordering::Relaxed);
+            } else if self.tx_queued.load(Ordering::Relaxed) > 0 {
+                self.tx_queued.fetch_sub(1, Ordering::Relaxed);
+                self.shifting.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    impl PlatPeri for MockUart {}
+
+    impl Configuration<UartConfig> for MockUart {
+        type Target = ();
+        fn configure(&self, _config: &UartConfig) -> blueos_hal::err::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Uart<UartConfig, (), InterruptType, UartCtrlStatus> for MockUart {}
+
+    impl Has8bitDataReg for MockUart {
+        fn read_data8(&self) -> blueos_hal::err::Result<u8> {
+            match self.rx.lock().pop_front() {
+                Some(Some(byte)) => Ok(byte),
+                Some(None) => Err(blueos_hal::err::HalError::Other("Parity Error")),
+                None => Ok(0),
+            }
+        }
+
+        fn write_data8(&self, _data: u8) {
+            self.tx_queued.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn is_data_ready(&self) -> bool {
+            false
+        }
+    }
+
+    impl HasLineStatusReg for MockUart {
+        fn is_bus_busy(&self) -> bool {
+            self.tick();
+            self.shifting.load(Ordering::Relaxed)
+        }
+    }
+
+    impl HasFifo for MockUart {
+        fn enable_fifo(&self, _num: u8) -> blueos_hal::err::Result<()> {
+            Ok(())
+        }
+
+        fn is_tx_fifo_full(&self) -> bool {
+            self.tx_queued.load(Ordering::Relaxed) >= MOCK_FIFO_DEPTH
+        }
+
+        fn is_rx_fifo_empty(&self) -> bool {
+            self.rx.lock().is_empty()
+        }
+
+        fn is_tx_fifo_empty(&self) -> bool {
+            self.tick();
+            self.tx_queued.load(Ordering::Relaxed) == 0
+        }
+    }
+
+    impl HasInterruptReg for MockUart {
+        type InterruptType = InterruptType;
+
+        fn enable_interrupt(&self, _intr: InterruptType) {}
+
+        fn disable_interrupt(&self, _intr: InterruptType) {}
+
+        fn get_interrupt(&self) -> InterruptType {
+            InterruptType::Unknown
+        }
+
+        fn enabled_interrupts(&self) -> InterruptType {
+            InterruptType::Unknown
+        }
+
+        fn set_interrupt_handler(&self, _handler: fn(usize), _ctx: usize) {}
+
+        fn clear_interrupt(&self, _intr: InterruptType) {}
+
+        fn get_irq_nums(&self) -> &[u32] {
+            &[]
+        }
+    }
+
+    #[test]
+    fn test_flush_drains_fifo_and_line() {
+        let uart = MockUart::leak();
+        let mut dev = UartDevice::new(uart, 0);
+        assert_eq!(dev.write(b"flush").unwrap(), 5);
+        dev.flush().unwrap();
+        assert_eq!(uart.tx_queued.load(Ordering::Relaxed), 0);
+        assert!(!uart.shifting.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_read_reports_line_error_after_data() {
+        let uart = MockUart::leak();
+        uart.rx.lock().extend([Some(b'a'), None, Some(b'b')]);
+        let mut dev = UartDevice::new(uart, 0);
+        let mut buf = [0u8; 4];
+        assert_eq!(dev.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], b'a');
+        assert_eq!(dev.read(&mut buf), Err(SerialError::Parity));
+        assert_eq!(dev.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], b'b');
+        assert_eq!(dev.read(&mut buf), Ok(0));
+    }
+
+    // RAM standing in for a PL011 register block, so that the line control
+    // programming can be checked on any board.
+    #[repr(C, align(4))]
+    struct FakePl011Regs([u32; 1024]);
+
+    fn fake_pl011() -> &'static ArmPl011<'static> {
+        let regs = Box::leak(Box::new(FakePl011Regs([0; 1024])));
+        let config = ArmPl011Config::new(regs as *mut FakePl011Regs as usize, 24_000_000);
+        Box::leak(Box::new(ArmPl011::new_with(config)))
+    }
+
+    fn setup_line_control(cflag: Cflags) -> LineControlRegister {
+        let uart = fake_pl011();
+        let mut termios = Termios::default();
+        termios.cflag = cflag | Cflags::CREAD;
+        UartDevice::new(uart, 0).setup(&termios).unwrap();
+        uart.dump_status().line_control
+    }
+
+    #[test]
+    fn test_pl011_8e1() {
+        assert_eq!(
+            setup_line_control(Cflags::CSIZE_8 | Cflags::PARENB),
+            LineControlRegister::WLEN_8BITS | LineControlRegister::PEN | LineControlRegister::EPS
+        );
+    }
+
+    #[test]
+    fn test_pl011_7o2() {
+        assert_eq!(
+            setup_line_control(Cflags::CSIZE_7 | Cflags::PARENB | Cflags::PARODD | Cflags::CSTOPB),
+            LineControlRegister::WLEN_7BITS | LineControlRegister::PEN | LineControlRegister::STP2
+        );
+    }
+
+    #[test]
+    fn test_pl011_stick_parity_and_stop_bits() {
+        let uart = fake_pl011();
+        let configure = |parity, data_bits, stop_bits| {
+            uart.configure(&UartConfig {
+                parity,
+                data_bits,
+                stop_bits,
+                ..UartConfig::default()
+            })
+            .map(|_| uart.dump_status().line_control)
+        };
+        assert_eq!(
+            configure(Parity::Mark, DataBits::DataBits8, StopBits::One),
+            Ok(LineControlRegister::WLEN_8BITS
+                | LineControlRegister::PEN
+                | LineControlRegister::SPS)
+        );
+        assert_eq!(
+            configure(Parity::Space, DataBits::DataBits8, StopBits::One),
+            Ok(LineControlRegister::WLEN_8BITS
+                | LineControlRegister::PEN
+                | LineControlRegister::EPS
+                | LineControlRegister::SPS)
+        );
+        assert_eq!(
+            configure(Parity::None, DataBits::DataBits5, StopBits::Two),
+            Ok(LineControlRegister::WLEN_5BITS | LineControlRegister::STP2)
+        );
+        assert!(configure(Parity::None, DataBits::DataBits5, StopBits::OnePointFive).is_err());
+    }
+
+    #[test]
+    fn test_pl011_enabled_interrupts() {
+        let uart = fake_pl011();
+        let mut dev = UartDevice::new(uart, 0);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::Unknown));
+        uart.enable_interrupt(InterruptType::Error);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::Error));
+        dev.set_rx_interrupt(true);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::Rx));
+        dev.set_tx_interrupt(true);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::All));
+        dev.set_rx_interrupt(false);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::Tx));
+        uart.enable_interrupt(InterruptType::RxTimeout);
+        assert!(matches!(uart.enabled_interrupts(), InterruptType::All));
+    }
 }
@@ -137,6 +137,8 @@ bitflags! {
         const PARENB = 0x100;
         // Odd parity, else even.
         const PARODD = 0x200;
+        // RTS/CTS hardware flow control.
+        const CRTSCTS = 0x8000_0000;
     }
 }
 
@@ -30,7 +30,7 @@ impl Plic {
     pub fn init(&self) {}
 
     pub fn set_priority(&self, irq: u32, prio: u32) {
-        assert!(irq > 0);
+        crate::kassert!(irq > 0, "PLIC irq 0 is reserved");
         unsafe { self.base.offset(irq as isize).write_volatile(prio) };
     }
 
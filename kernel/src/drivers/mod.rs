@@ -15,3 +15,4 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 pub(crate) mod ic;
+pub mod sampler;
@@ -0,0 +1,120 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic sampling of sensors into a [`Topic`].
+//!
+//! [`spawn`] starts a thread that calls a measurement at a fixed period and
+//! publishes every result, which turns a one-shot driver into a continuous
+//! data source. Releases are at fixed ticks, so the period does not drift
+//! with the measurement time. A measurement that overruns its period does
+//! not queue up the missed releases: they are skipped, counted in
+//! [`Sampler::skipped`], and sampling continues at the next release.
+
+use crate::{
+    scheduler,
+    thread::{self, ThreadNode},
+    time::{self, Duration},
+    types::Arc,
+};
+use blueos_infra::pubsub::Topic;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Sampler {
+    thread: ThreadNode,
+    skipped: Arc<AtomicUsize>,
+}
+
+impl Sampler {
+    pub fn thread(&self) -> &ThreadNode {
+        &self.thread
+    }
+
+    /// Releases skipped because a measurement overran its period.
+    pub fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+/// Calls `measure` every `period` on a new thread and publishes each `Some`
+/// result to `topic`. A `None` is a failed measurement and publishes
+/// nothing.
+///
+/// Returns `None` if `topic` already has a publisher or the thread could not
+/// be started.
+pub fn spawn<T, const N: usize, F>(
+    topic: &'static Topic<T, N>,
+    period: Duration,
+    mut measure: F,
+) -> Option<Sampler>
+where
+    T: Copy + Send + 'static,
+    F: FnMut() -> Option<T> + Send + 'static,
+{
+    let mut publisher = topic.publisher()?;
+    let period = period.as_ticks().max(1);
+    let skipped = Arc::new(AtomicUsize::new(0));
+    let counter = skipped.clone();
+    let thread = thread::spawn(move || {
+        let mut release = time::get_sys_ticks();
+        loop {
+            if let Some(value) = measure() {
+                publisher.publish(value);
+            }
+            let now = time::get_sys_ticks();
+            let (next, missed) = next_release(release, now, period);
+            if missed != 0 {
+                counter.fetch_add(missed, Ordering::Relaxed);
+            }
+            release = next;
+            match release.wrapping_sub(now) {
+                0 => scheduler::yield_me(),
+                ticks => scheduler::suspend_me_for(ticks),
+            }
+        }
+    })?;
+    Some(Sampler { thread, skipped })
+}
+
+// The release following `release` that is not before `now`, and the number
+// of releases in between that have already passed.
+fn next_release(release: usize, now: usize, period: usize) -> (usize, usize) {
+    let late = now.wrapping_sub(release);
+    // Releases are at most a period apart, so a difference past half the
+    // tick range means `now` is still before `release`.
+    if late > usize::MAX / 2 {
+        return (release, 0);
+    }
+    let passed = late.div_ceil(period).max(1);
+    (release.wrapping_add(passed * period), passed - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueos_test_macro::test;
+
+    #[test]
+    fn test_next_release() {
+        // Measurement finished within the period.
+        assert_eq!(next_release(100, 103, 10), (110, 0));
+        // Took no time at all.
+        assert_eq!(next_release(100, 100, 10), (110, 0));
+        // Finished right on the next release, which is still on time.
+        assert_eq!(next_release(100, 110, 10), (110, 0));
+        // Overran two and a half periods.
+        assert_eq!(next_release(100, 125, 10), (130, 2));
+        // Across the tick counter wrapping.
+        assert_eq!(next_release(usize::MAX - 4, 2, 10), (5, 0));
+    }
+}
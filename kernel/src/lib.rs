@@ -74,6 +74,8 @@ pub mod sync;
 pub mod syscall_handlers;
 pub mod thread;
 pub mod time;
+#[cfg(event_trace)]
+pub mod trace;
 pub mod types;
 #[cfg(enable_vfs)]
 pub mod vfs;
@@ -107,6 +109,33 @@ macro_rules! trace {
     }};
 }
 
+/// Records a binary event into the [`trace`] ring, with up to two
+/// `usize`-convertible arguments. Compiles to nothing unless `EVENT_TRACE` is
+/// enabled.
+#[cfg(event_trace)]
+#[macro_export]
+macro_rules! trace_event {
+    ($id:expr) => {
+        $crate::trace_event!($id, 0, 0)
+    };
+    ($id:expr, $a:expr) => {
+        $crate::trace_event!($id, $a, 0)
+    };
+    ($id:expr, $a:expr, $b:expr) => {
+        $crate::trace::record($id, $a as usize, $b as usize)
+    };
+}
+
+#[cfg(not(event_trace))]
+#[macro_export]
+macro_rules! trace_event {
+    ($id:expr $(, $arg:expr)* $(,)?) => {{
+        if false {
+            let _ = ($id, $($arg),*);
+        }
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     extern crate alloc;
@@ -252,6 +281,20 @@ mod tests {
         assert_eq!(*r, 1);
     }
 
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_spinlock_held_count() {
+        let lock = sync::spinlock::SpinLock::new(0);
+        let held = scheduler::current_thread().held_spinlocks();
+        let w = lock.lock();
+        assert_eq!(scheduler::current_thread().held_spinlocks(), held + 1);
+        drop(w);
+        let r = lock.irqsave_read();
+        assert_eq!(scheduler::current_thread().held_spinlocks(), held + 1);
+        drop(r);
+        assert_eq!(scheduler::current_thread().held_spinlocks(), held);
+    }
+
     #[test]
     fn test_spinlock_loop() {
         let lock = sync::spinlock::SpinLock::new(0);
@@ -286,6 +329,11 @@ mod tests {
         assert!(arch::local_irq_enabled());
     }
 
+    #[test]
+    fn test_in_interrupt() {
+        assert!(!support::in_interrupt());
+    }
+
     #[test]
     fn stress_trap() {
         #[cfg(target_pointer_width = "32")]
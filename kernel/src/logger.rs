@@ -15,12 +15,15 @@
 use crate::{
     arch, kprintln, scheduler, sync::SpinLock, thread::Thread, time::tick_get_millisecond,
 };
-use log::{LevelFilter, Metadata, Record};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use log::{Level, LevelFilter, Metadata, Record};
+use spin::Once;
 
 static LOGGER_MUTEX: SpinLock<()> = SpinLock::new(());
 
 struct Logger;
 
+#[derive(Clone, Copy)]
 pub enum LogLevel {
     Trace,
     Debug,
@@ -29,15 +32,106 @@ pub enum LogLevel {
     Error,
 }
 
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => Level::Trace,
+            LogLevel::Debug => Level::Debug,
+            LogLevel::Info => Level::Info,
+            LogLevel::Warn => Level::Warn,
+            LogLevel::Error => Level::Error,
+        }
+    }
+}
+
+/// An additional destination for log records, e.g. a second UART or a
+/// debug probe channel. The console is always logged to.
+pub trait LogSink: Sync {
+    /// Called with every record at or above the sink's level. Records below
+    /// the global [`set_max_level`] never reach any sink.
+    fn log(&self, record: &Record);
+}
+
+const MAX_SINKS: usize = 4;
+
+struct SinkSlot {
+    sink: Once<&'static dyn LogSink>,
+    level: AtomicUsize,
+}
+
+impl SinkSlot {
+    const fn new() -> Self {
+        Self {
+            sink: Once::new(),
+            level: AtomicUsize::new(0),
+        }
+    }
+
+    fn set(&self, sink: &'static dyn LogSink, level: LogLevel) {
+        self.level
+            .store(Level::from(level) as usize, Ordering::Relaxed);
+        self.sink.call_once(|| sink);
+    }
+
+    fn log(&self, record: &Record) {
+        // A slot being registered concurrently is not ready yet.
+        let Some(sink) = self.sink.get() else {
+            return;
+        };
+        if record.level() as usize <= self.level.load(Ordering::Relaxed) {
+            sink.log(record);
+        }
+    }
+}
+
+// Slots are only ever added, so fanning out needs no lock.
+static SINKS: [SinkSlot; MAX_SINKS] = [const { SinkSlot::new() }; MAX_SINKS];
+static NUM_SINKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Registers `sink` for records at `level` or more severe. Returns false if
+/// all `MAX_SINKS` slots are taken.
+pub fn add_sink(sink: &'static dyn LogSink, level: LogLevel) -> bool {
+    let i = NUM_SINKS.fetch_add(1, Ordering::Relaxed);
+    if i >= MAX_SINKS {
+        NUM_SINKS.store(MAX_SINKS, Ordering::Relaxed);
+        return false;
+    }
+    SINKS[i].set(sink, level);
+    true
+}
+
+fn log_to_sinks(record: &Record) {
+    let n = NUM_SINKS.load(Ordering::Relaxed).min(MAX_SINKS);
+    for slot in &SINKS[..n] {
+        slot.log(record);
+    }
+}
+
+/// Forwards records to defmt, so they reach the probe over the defmt
+/// transport next to records logged with `defmt::info!` and friends.
+///
+/// The `log` arguments are still formatted on the target. Code that wants
+/// defmt's deferred formatting has to call the defmt macros directly.
+#[cfg(use_defmt)]
+pub struct DefmtSink;
+
+#[cfg(use_defmt)]
+impl LogSink for DefmtSink {
+    fn log(&self, record: &Record) {
+        let args = defmt::Display2Format(record.args());
+        match record.level() {
+            Level::Error => defmt::error!("{}", args),
+            Level::Warn => defmt::warn!("{}", args),
+            Level::Info => defmt::info!("{}", args),
+            Level::Debug => defmt::debug!("{}", args),
+            Level::Trace => defmt::trace!("{}", args),
+        }
+    }
+}
+
 ///set max log level
 pub fn set_max_level(level: LogLevel) {
-    match level {
-        LogLevel::Trace => log::set_max_level(LevelFilter::Trace),
-        LogLevel::Debug => log::set_max_level(LevelFilter::Debug),
-        LogLevel::Info => log::set_max_level(LevelFilter::Info),
-        LogLevel::Warn => log::set_max_level(LevelFilter::Warn),
-        LogLevel::Error => log::set_max_level(LevelFilter::Error),
-    }
+    log::set_max_level(Level::from(level).to_level_filter());
 }
 
 /// log init
@@ -48,6 +142,8 @@ pub fn logger_init() {
     #[cfg(release)]
     log::set_max_level(LevelFilter::Warn);
     log::set_logger(&LOGGER).unwrap();
+    #[cfg(use_defmt)]
+    add_sink(&DefmtSink, LogLevel::Trace);
 }
 
 ///impl log for Logger
@@ -63,7 +159,7 @@ impl log::Log for Logger {
         let timestamp = tick_get_millisecond();
         let tid = scheduler::current_thread_id();
         let cpu = arch::current_cpu_id();
-        let _guard = LOGGER_MUTEX.irqsave_lock();
+        let guard = LOGGER_MUTEX.irqsave_lock();
         kprintln!(
             "[T:{:09} C:{} TH:0x{:x}][{}] {} ",
             timestamp,
@@ -72,7 +168,39 @@ impl log::Log for Logger {
             record.level(),
             record.args()
         );
+        drop(guard);
+        log_to_sinks(record);
     }
 
     fn flush(&self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueos_test_macro::test;
+
+    struct CountingSink(AtomicUsize);
+
+    impl LogSink for CountingSink {
+        fn log(&self, _record: &Record) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Uses a slot of its own, so the sink isn't left in `SINKS` for the
+    // rest of the run.
+    #[test]
+    fn test_log_sink_level() {
+        static SINK: CountingSink = CountingSink(AtomicUsize::new(0));
+        let slot = SinkSlot::new();
+        slot.log(&Record::builder().level(Level::Error).build());
+        assert_eq!(SINK.0.load(Ordering::Relaxed), 0);
+
+        slot.set(&SINK, LogLevel::Warn);
+        for level in [Level::Info, Level::Warn, Level::Error] {
+            slot.log(&Record::builder().level(level).build());
+        }
+        assert_eq!(SINK.0.load(Ordering::Relaxed), 2);
+    }
+}
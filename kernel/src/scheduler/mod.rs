@@ -343,6 +343,13 @@ pub fn yield_me() {
     // The scheduler assumes every thread should be resumed with local
     // irq enabled.
     assert!(arch::local_irq_enabled());
+    // Whoever spins on the lock we hold may be the thread we are yielding to.
+    #[cfg(debug_assertions)]
+    assert_eq!(
+        current_thread().held_spinlocks(),
+        0,
+        "yielding while holding a SpinLock"
+    );
     let pg = thread::Thread::try_preempt_me();
     if !pg.preemptable() {
         arch::idle();
@@ -391,6 +391,23 @@ impl SmpStagedInit {
     }
 }
 
+/// Whether the current CPU is running an exception or interrupt handler.
+///
+/// Drivers use this to pick a spinning path over a blocking one. Cortex-M
+/// reads the active exception number from IPSR, so faults and SVCalls count
+/// too; other architectures rely on the IRQ nesting count.
+#[inline]
+pub fn in_interrupt() -> bool {
+    #[cfg(target_arch = "arm")]
+    {
+        arch::is_in_interrupt()
+    }
+    #[cfg(not(target_arch = "arm"))]
+    {
+        crate::irq::is_in_irq()
+    }
+}
+
 #[inline(always)]
 pub fn sideeffect() {
     unsafe { core::arch::asm!("") }
@@ -406,6 +423,43 @@ macro_rules! static_assert {
     };
 }
 
+/// Asserts a runtime invariant, following the kconfig `ASSERT_POLICY`.
+///
+/// With `ASSERT_POLICY_PANIC` a failed `kassert!` panics like `assert!`.
+/// With `ASSERT_POLICY_LOG` it logs the failure and execution continues, and
+/// with `ASSERT_POLICY_TRAP` it logs and then stops at a debugger breakpoint.
+/// Only use it where carrying on is survivable; invariants guarding memory
+/// safety should keep using `assert!`.
+#[macro_export]
+macro_rules! kassert {
+    ($cond:expr $(,)?) => {
+        $crate::kassert!($cond, "{}", core::stringify!($cond))
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::support::assert_failed(core::format_args!($($arg)+));
+        }
+    };
+}
+
+#[doc(hidden)]
+#[cold]
+#[track_caller]
+pub fn assert_failed(args: core::fmt::Arguments) {
+    #[cfg(not(any(assert_policy_log, assert_policy_trap)))]
+    panic!("assertion failed: {}", args);
+    #[cfg(any(assert_policy_log, assert_policy_trap))]
+    log::error!(
+        "assertion failed: {} at {}",
+        args,
+        core::panic::Location::caller()
+    );
+    #[cfg(assert_policy_trap)]
+    unsafe {
+        core::intrinsics::breakpoint()
+    };
+}
+
 pub(crate) fn show_current_heap_usage() {
     log::info!("Current heap: {:?}", crate::allocator::memory_info());
 }
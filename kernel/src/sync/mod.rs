@@ -16,11 +16,13 @@ pub mod atomic_wait;
 pub use atomic_wait::{atomic_wait, atomic_wake};
 pub mod mqueue;
 pub mod mutex;
+pub mod oneshot;
 pub mod posix_mqueue;
 pub mod semaphore;
 pub mod spinlock;
 pub use mqueue::MessageQueue;
 pub use mutex::Mutex;
+pub use oneshot::OneShot;
 pub use semaphore::Semaphore;
 pub use spinlock::{ISpinLock, SpinLock, SpinLockGuard, SpinLockReadGuard, SpinLockWriteGuard};
 pub mod barrier;
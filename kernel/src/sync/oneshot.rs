@@ -0,0 +1,168 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::SpinLock;
+use crate::{
+    irq, scheduler,
+    scheduler::{InsertMode, WaitQueue},
+    thread,
+    time::WAITING_FOREVER,
+};
+use core::cell::Cell;
+
+/// Single-value completion channel, typically signaled from an ISR when a
+/// transfer finishes and waited on by the thread that started it.
+///
+/// A value signaled before anyone waits is kept until it is taken. Only the
+/// first `signal` is accepted until the channel is [`reset`](Self::reset).
+pub struct OneShot<T> {
+    // We let the Spinlock protect the whole OneShot.
+    pending: SpinLock<WaitQueue>,
+    value: Cell<Option<T>>,
+    signaled: Cell<bool>,
+}
+
+impl<T> Default for OneShot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OneShot<T> {
+    pub const fn new() -> Self {
+        Self {
+            pending: SpinLock::new(WaitQueue::new()),
+            value: Cell::new(None),
+            signaled: Cell::new(false),
+        }
+    }
+
+    pub fn init(&self) -> bool {
+        let mut w = self.pending.irqsave_lock();
+        w.init()
+    }
+
+    /// Stores `value` and wakes the waiter. Safe to call from an ISR.
+    ///
+    /// Returns the value back if the channel has already been signaled.
+    pub fn signal(&self, value: T) -> Result<(), T> {
+        let mut w = self.pending.irqsave_lock();
+        if self.signaled.get() {
+            return Err(value);
+        }
+        self.signaled.set(true);
+        self.value.set(Some(value));
+        while let Some(next) = w.pop_front() {
+            let t = next.thread.clone();
+            if let Some(timer) = &t.timer {
+                timer.stop();
+            }
+            if scheduler::queue_ready_thread(thread::SUSPENDED, t) {
+                break;
+            }
+        }
+        drop(w);
+        scheduler::yield_me_now_or_later();
+        Ok(())
+    }
+
+    pub fn is_signaled(&self) -> bool {
+        let _w = self.pending.irqsave_lock();
+        self.signaled.get()
+    }
+
+    /// Takes the value if it has been signaled, without blocking.
+    pub fn try_take(&self) -> Option<T> {
+        let _w = self.pending.irqsave_lock();
+        self.value.take()
+    }
+
+    /// Blocks until the value is signaled and takes it.
+    pub fn wait(&self) -> T {
+        loop {
+            if let Some(v) = self.wait_timeout(WAITING_FOREVER) {
+                return v;
+            }
+        }
+    }
+
+    /// Blocks for at most `ticks` until the value is signaled and takes it.
+    pub fn wait_timeout(&self, ticks: usize) -> Option<T> {
+        assert!(!irq::is_in_irq());
+        let w = self.pending.irqsave_lock();
+        if let Some(v) = self.value.take() {
+            return Some(v);
+        }
+        if ticks == 0 {
+            return None;
+        }
+        scheduler::suspend_me_with_timeout(w, ticks, InsertMode::InsertToEnd);
+        let _w = self.pending.irqsave_lock();
+        self.value.take()
+    }
+
+    /// Makes the channel ready for the next `signal`, dropping any value
+    /// that has not been taken.
+    pub fn reset(&self) {
+        let w = self.pending.irqsave_lock();
+        let old = self.value.take();
+        self.signaled.set(false);
+        drop(w);
+        drop(old);
+    }
+}
+
+impl<T> !Send for OneShot<T> {}
+unsafe impl<T: Send> Sync for OneShot<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Arc;
+    use blueos_test_macro::test;
+
+    #[test]
+    fn test_oneshot_signal_before_wait() {
+        let oneshot = OneShot::new();
+        assert!(oneshot.init());
+        assert_eq!(oneshot.try_take(), None);
+        assert_eq!(oneshot.signal(7), Ok(()));
+        assert_eq!(oneshot.signal(8), Err(8));
+        assert!(oneshot.is_signaled());
+        assert_eq!(oneshot.wait(), 7);
+        assert_eq!(oneshot.wait_timeout(0), None);
+
+        oneshot.reset();
+        assert_eq!(oneshot.signal(9), Ok(()));
+        assert_eq!(oneshot.try_take(), Some(9));
+    }
+
+    #[test]
+    fn test_oneshot_wait_timeout() {
+        let oneshot = OneShot::<u32>::new();
+        oneshot.init();
+        assert_eq!(oneshot.wait_timeout(10), None);
+    }
+
+    #[test]
+    fn test_oneshot_multi_thread() {
+        let oneshot = Arc::new(OneShot::new());
+        oneshot.init();
+        let signaler = oneshot.clone();
+        let _ = thread::spawn(move || {
+            assert_eq!(signaler.signal(42usize), Ok(()));
+        });
+        assert_eq!(oneshot.wait(), 42);
+    }
+}
@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(debug_assertions)]
+use crate::thread::ThreadNode;
 use crate::{
+    arch, scheduler, support,
     support::DisableInterruptGuard,
     types::{IRwLock, IntrusiveAdapter, NestedAdapter, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
@@ -22,6 +25,36 @@ use core::{
     sync::atomic::{compiler_fence, Ordering},
 };
 
+// Counts the guard against the thread that created it, so that a thread
+// yielding with a spin lock held can be caught. The guard may be dropped by
+// another thread after a context switch, hence the thread is remembered.
+#[cfg(debug_assertions)]
+#[derive(Debug)]
+struct HeldToken(Option<ThreadNode>);
+
+#[cfg(debug_assertions)]
+impl HeldToken {
+    #[inline]
+    fn new() -> Self {
+        if !scheduler::is_schedule_ready() {
+            return Self(None);
+        }
+        let t = scheduler::current_thread();
+        t.inc_held_spinlocks();
+        Self(Some(t))
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for HeldToken {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(t) = &self.0 {
+            t.dec_held_spinlocks();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SpinLock<T: ?Sized> {
     lock: RwLock<T>,
@@ -35,6 +68,8 @@ pub type SpinLockWriteGuard<'a, T> = SpinLockGuard<'a, T>;
 pub struct SpinLockGuard<'a, T: ?Sized> {
     lock_guard: RwLockWriteGuard<'a, T>,
     irq_guard: Option<DisableInterruptGuard>,
+    #[cfg(debug_assertions)]
+    held: HeldToken,
 }
 
 impl<T: ?Sized> SpinLockGuard<'_, T> {
@@ -64,6 +99,8 @@ impl<'a, T: 'a + ?Sized> DerefMut for SpinLockGuard<'a, T> {
 pub struct SpinLockReadGuard<'a, T: ?Sized> {
     lock_guard: RwLockReadGuard<'a, T>,
     irq_guard: Option<DisableInterruptGuard>,
+    #[cfg(debug_assertions)]
+    held: HeldToken,
 }
 
 impl<T: ?Sized> SpinLockReadGuard<'_, T> {
@@ -118,6 +155,8 @@ impl<T: ?Sized> SpinLock<T> {
         Some(SpinLockGuard {
             irq_guard: None,
             lock_guard,
+            #[cfg(debug_assertions)]
+            held: HeldToken::new(),
         })
     }
 
@@ -131,6 +170,35 @@ impl<T: ?Sized> SpinLock<T> {
         }
     }
 
+    /// Spins up to `SPINLOCK_ADAPTIVE_SPINS` times, for a holder that is
+    /// about to release the lock, e.g. an ISR, then yields to let a holder
+    /// that has been preempted run. In interrupt context, with interrupts
+    /// disabled or before the scheduler starts, it keeps spinning.
+    ///
+    /// The caller must not hold any other spin lock.
+    pub fn adaptive_lock(&self) -> SpinLockGuard<'_, T> {
+        let mut spins = 0;
+        loop {
+            if let Some(l) = self.try_lock() {
+                return l;
+            }
+            if spins < blueos_kconfig::SPINLOCK_ADAPTIVE_SPINS {
+                spins += 1;
+                core::hint::spin_loop();
+                continue;
+            }
+            if support::in_interrupt()
+                || !arch::local_irq_enabled()
+                || !scheduler::is_schedule_ready()
+            {
+                core::hint::spin_loop();
+                continue;
+            }
+            spins = 0;
+            scheduler::yield_me();
+        }
+    }
+
     #[inline]
     pub fn write(&self) -> SpinLockWriteGuard<'_, T> {
         self.lock()
@@ -155,6 +223,8 @@ impl<T: ?Sized> SpinLock<T> {
         Some(SpinLockReadGuard {
             irq_guard: None,
             lock_guard,
+            #[cfg(debug_assertions)]
+            held: HeldToken::new(),
         })
     }
 
@@ -239,6 +309,8 @@ impl<T: Sized, A: const IntrusiveAdapter<T>> ISpinLock<T, A> {
         SpinLockGuard {
             lock_guard: l,
             irq_guard: None,
+            #[cfg(debug_assertions)]
+            held: HeldToken::new(),
         }
     }
 
@@ -257,6 +329,8 @@ impl<T: Sized, A: const IntrusiveAdapter<T>> ISpinLock<T, A> {
         SpinLockReadGuard {
             lock_guard: l,
             irq_guard: None,
+            #[cfg(debug_assertions)]
+            held: HeldToken::new(),
         }
     }
 
@@ -163,6 +163,9 @@ pub struct Thread {
     origin_priority: ThreadPriority,
     state: AtomicUint,
     preempt_count: AtomicUint,
+    // Number of spin lock guards created by this thread that are alive.
+    #[cfg(debug_assertions)]
+    held_spinlocks: AtomicUint,
     #[cfg(robin_scheduler)]
     robin_count: AtomicI32,
     // FIXME: Using a rusty lock looks not flexible. Now we are using
@@ -394,6 +397,8 @@ impl Thread {
             priority: 0,
             origin_priority: 0,
             preempt_count: AtomicUint::new(0),
+            #[cfg(debug_assertions)]
+            held_spinlocks: AtomicUint::new(0),
             posix_compat: None,
             stats: ThreadStats::new(),
             timer: None,
@@ -588,6 +593,24 @@ impl Thread {
         self.preempt_count.load(Ordering::Relaxed)
     }
 
+    #[cfg(debug_assertions)]
+    #[inline]
+    pub(crate) fn inc_held_spinlocks(&self) {
+        self.held_spinlocks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[cfg(debug_assertions)]
+    #[inline]
+    pub(crate) fn dec_held_spinlocks(&self) {
+        self.held_spinlocks.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    #[cfg(debug_assertions)]
+    #[inline]
+    pub fn held_spinlocks(&self) -> Uint {
+        self.held_spinlocks.load(Ordering::Relaxed)
+    }
+
     #[cfg(robin_scheduler)]
     #[inline]
     pub fn round_robin(&self, ticks: usize) -> i32 {
@@ -0,0 +1,143 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use blueos_kconfig::TICKS_PER_SECOND;
+
+const MICROS_PER_SEC: u128 = 1_000_000;
+
+/// A span of time with microsecond resolution.
+///
+/// This is the one place where wall-clock units are turned into system ticks.
+/// All conversions saturate instead of overflowing, and converting to ticks
+/// rounds up so that a timeout never expires early.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    micros: u64,
+}
+
+impl Duration {
+    pub const ZERO: Self = Self { micros: 0 };
+    pub const MAX: Self = Self { micros: u64::MAX };
+
+    pub const fn from_micros(micros: u64) -> Self {
+        Self { micros }
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self {
+            micros: millis.saturating_mul(1_000),
+        }
+    }
+
+    pub const fn from_secs(secs: u64) -> Self {
+        Self {
+            micros: secs.saturating_mul(1_000_000),
+        }
+    }
+
+    pub fn from_ticks(ticks: usize) -> Self {
+        let micros = ticks as u128 * MICROS_PER_SEC / TICKS_PER_SECOND as u128;
+        Self {
+            micros: micros.min(u64::MAX as u128) as u64,
+        }
+    }
+
+    pub const fn as_micros(&self) -> u64 {
+        self.micros
+    }
+
+    pub const fn as_millis(&self) -> u64 {
+        self.micros / 1_000
+    }
+
+    /// Number of ticks covering this duration, rounded up.
+    pub fn as_ticks(&self) -> usize {
+        let ticks = (self.micros as u128 * TICKS_PER_SECOND as u128).div_ceil(MICROS_PER_SEC);
+        ticks.min(usize::MAX as u128) as usize
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.micros == 0
+    }
+
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self {
+            micros: self.micros.saturating_add(rhs.micros),
+        }
+    }
+
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self {
+            micros: self.micros.saturating_sub(rhs.micros),
+        }
+    }
+}
+
+impl From<core::time::Duration> for Duration {
+    fn from(d: core::time::Duration) -> Self {
+        Self {
+            micros: d.as_micros().min(u64::MAX as u128) as u64,
+        }
+    }
+}
+
+impl From<Duration> for core::time::Duration {
+    fn from(d: Duration) -> Self {
+        core::time::Duration::from_micros(d.micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueos_test_macro::test;
+
+    #[test]
+    fn test_duration_units() {
+        assert_eq!(Duration::from_millis(3).as_micros(), 3_000);
+        assert_eq!(Duration::from_secs(2).as_millis(), 2_000);
+        assert_eq!(Duration::from_micros(1_999).as_millis(), 1);
+        assert_eq!(Duration::from_secs(u64::MAX), Duration::MAX);
+        assert_eq!(Duration::from_millis(u64::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn test_duration_ticks() {
+        assert_eq!(Duration::ZERO.as_ticks(), 0);
+        assert_eq!(Duration::from_secs(1).as_ticks(), TICKS_PER_SECOND);
+        // Anything shorter than a tick still waits for one.
+        assert_eq!(Duration::from_micros(1).as_ticks(), 1);
+        assert_eq!(
+            Duration::from_ticks(TICKS_PER_SECOND),
+            Duration::from_secs(1)
+        );
+        let d = Duration::from_ticks(7);
+        assert_eq!(d.as_ticks(), 7);
+    }
+
+    #[test]
+    fn test_duration_saturates() {
+        // Must not overflow on either side of the conversion.
+        assert!(Duration::MAX.as_ticks() >= Duration::from_secs(1).as_ticks());
+        assert!(Duration::from_ticks(usize::MAX) >= Duration::from_ticks(usize::MAX / 2));
+        assert_eq!(
+            Duration::MAX.saturating_add(Duration::from_micros(1)),
+            Duration::MAX
+        );
+        assert_eq!(
+            Duration::ZERO.saturating_sub(Duration::from_micros(1)),
+            Duration::ZERO
+        );
+    }
+}
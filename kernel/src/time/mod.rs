@@ -12,9 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod duration;
+pub mod source;
 pub mod systick;
 pub mod timer;
 
+pub use duration::Duration;
+pub use source::Deadline;
+
 use crate::{arch, boards, scheduler, support::DisableInterruptGuard, thread::Thread};
 use blueos_kconfig::TICKS_PER_SECOND;
 use systick::SYSTICK;
@@ -0,0 +1,242 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pluggable clock for delay and timeout helpers.
+//!
+//! Code that spins on a timeout takes a [`TimeSource`] instead of reading the
+//! systick directly, so tests can drive time with [`MockTime`]. A bounded
+//! loop creates a [`Deadline`] up front and checks it on every iteration.
+
+use super::{systick::SYSTICK, Duration};
+use blueos_kconfig::TICKS_PER_SECOND;
+#[cfg(test)]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub trait TimeSource {
+    /// Current time in system ticks.
+    fn now(&self) -> usize;
+
+    /// A counter running at [`counter_hz`](Self::counter_hz), finer than
+    /// the ticks if the clock has one. Defaults to the tick count.
+    fn counter(&self) -> u64 {
+        self.now() as u64
+    }
+
+    fn counter_hz(&self) -> u64 {
+        TICKS_PER_SECOND as u64
+    }
+}
+
+/// The system tick counter.
+pub struct SysTick;
+
+impl TimeSource for SysTick {
+    fn now(&self) -> usize {
+        super::get_sys_ticks()
+    }
+
+    // The cycle counter is only usable once the systick is set up.
+    fn counter(&self) -> u64 {
+        if SYSTICK.get_step() == 0 {
+            self.now() as u64
+        } else {
+            super::get_sys_cycles()
+        }
+    }
+
+    fn counter_hz(&self) -> u64 {
+        match SYSTICK.get_step() {
+            0 => TICKS_PER_SECOND as u64,
+            step => (step * TICKS_PER_SECOND) as u64,
+        }
+    }
+}
+
+/// A point in time a bounded wait gives up at.
+///
+/// The timeout is converted into counter units of the clock once, rounded
+/// up, so polling is a counter read and a compare. On [`SysTick`] this uses
+/// the cycle counter, which resolves timeouts shorter than a tick.
+pub struct Deadline<'a, C: TimeSource + ?Sized = SysTick> {
+    clock: &'a C,
+    start: u64,
+    span: u64,
+    hz: u64,
+}
+
+impl Deadline<'static> {
+    /// Deadline `timeout` from now on the system clock.
+    pub fn after(timeout: Duration) -> Self {
+        Self::new(&SysTick, timeout)
+    }
+}
+
+impl<'a, C: TimeSource + ?Sized> Deadline<'a, C> {
+    pub fn new(clock: &'a C, timeout: Duration) -> Self {
+        let hz = clock.counter_hz();
+        let span = (timeout.as_micros() as u128 * hz as u128).div_ceil(1_000_000);
+        Self {
+            clock,
+            start: clock.counter(),
+            span: span.min(u64::MAX as u128) as u64,
+            hz,
+        }
+    }
+
+    #[inline]
+    fn elapsed(&self) -> u64 {
+        self.clock.counter().wrapping_sub(self.start)
+    }
+
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.elapsed() >= self.span
+    }
+
+    /// Time left until the deadline, zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        let left = self.span.saturating_sub(self.elapsed()) as u128;
+        Duration::from_micros((left * 1_000_000 / self.hz as u128).min(u64::MAX as u128) as u64)
+    }
+}
+
+/// Calls `cond` until it returns `true` or `timeout` has elapsed on `clock`.
+/// Returns whether `cond` was satisfied.
+///
+/// `cond` is always evaluated at least once, even with a zero timeout.
+pub fn poll_until<C, F>(clock: &C, timeout: Duration, mut cond: F) -> bool
+where
+    C: TimeSource + ?Sized,
+    F: FnMut() -> bool,
+{
+    let deadline = Deadline::new(clock, timeout);
+    loop {
+        if cond() {
+            return true;
+        }
+        if deadline.is_expired() {
+            return false;
+        }
+        core::hint::spin_loop();
+    }
+}
+
+/// Busy-waits for `duration` on `clock`.
+pub fn delay<C: TimeSource + ?Sized>(clock: &C, duration: Duration) {
+    poll_until(clock, duration, || false);
+}
+
+/// A clock controlled by the test.
+///
+/// Besides explicit [`MockTime::advance`], the clock can move forward by a
+/// fixed step on every read, which lets polling loops time out without a
+/// second thread.
+#[cfg(test)]
+pub struct MockTime {
+    now: AtomicUsize,
+    step: usize,
+}
+
+#[cfg(test)]
+impl MockTime {
+    pub const fn new() -> Self {
+        Self::with_step(0)
+    }
+
+    pub const fn with_step(step: usize) -> Self {
+        Self {
+            now: AtomicUsize::new(0),
+            step,
+        }
+    }
+
+    pub fn advance(&self, ticks: usize) {
+        self.now.fetch_add(ticks, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+impl TimeSource for MockTime {
+    fn now(&self) -> usize {
+        self.now.fetch_add(self.step, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueos_test_macro::test;
+
+    #[test]
+    fn test_poll_until_satisfied() {
+        let clock = MockTime::new();
+        let mut n = 0;
+        assert!(poll_until(&clock, Duration::from_secs(1), || {
+            n += 1;
+            n == 3
+        }));
+        assert_eq!(n, 3);
+    }
+
+    #[test]
+    fn test_poll_until_times_out() {
+        let clock = MockTime::new();
+        let timeout = Duration::from_secs(1);
+        let mut polls = 0;
+        assert!(!poll_until(&clock, timeout, || {
+            polls += 1;
+            clock.advance(timeout.as_ticks() / 4);
+            false
+        }));
+        assert_eq!(polls, 4);
+    }
+
+    #[test]
+    fn test_poll_until_zero_timeout() {
+        let clock = MockTime::new();
+        let mut polls = 0;
+        assert!(!poll_until(&clock, Duration::ZERO, || {
+            polls += 1;
+            false
+        }));
+        assert_eq!(polls, 1);
+    }
+
+    #[test]
+    fn test_deadline() {
+        let clock = MockTime::new();
+        let deadline = Deadline::new(&clock, Duration::from_ticks(4));
+        assert!(!deadline.is_expired());
+        assert_eq!(deadline.remaining(), Duration::from_ticks(4));
+        clock.advance(1);
+        assert_eq!(deadline.remaining(), Duration::from_ticks(3));
+        clock.advance(3);
+        assert!(deadline.is_expired());
+        clock.advance(1);
+        assert_eq!(deadline.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_deadline_zero() {
+        let clock = MockTime::new();
+        assert!(Deadline::new(&clock, Duration::ZERO).is_expired());
+    }
+
+    #[test]
+    fn test_delay_with_step() {
+        let clock = MockTime::with_step(1);
+        delay(&clock, Duration::from_ticks(10));
+        assert!(clock.now() >= 10);
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (c) 2025 vivo Mobile Communication Co., Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//       http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Binary event ring, a flight recorder for post-mortem analysis.
+//!
+//! [`trace_event!`](crate::trace_event) stores a fixed-size [`Event`] into a
+//! preallocated ring without locking or formatting, so it is cheap enough to
+//! be used in interrupt handlers without perturbing their timing. The fault
+//! handlers [`dump`] the ring before panicking.
+
+use crate::time;
+use blueos_kconfig::EVENT_TRACE_ENTRIES;
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+const _: () = assert!(
+    EVENT_TRACE_ENTRIES.is_power_of_two(),
+    "EVENT_TRACE_ENTRIES must be a power of two"
+);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    pub cycles: u64,
+    pub id: u32,
+    pub args: [usize; 2],
+}
+
+struct Ring {
+    // Total number of events ever recorded.
+    head: AtomicUsize,
+    slots: [UnsafeCell<Event>; EVENT_TRACE_ENTRIES],
+}
+
+// Writers own their slot through `head`. Readers may observe a torn event if
+// the ring wraps while they read, which is acceptable for diagnostics.
+unsafe impl Sync for Ring {}
+
+static RING: Ring = Ring {
+    head: AtomicUsize::new(0),
+    slots: [const {
+        UnsafeCell::new(Event {
+            cycles: 0,
+            id: 0,
+            args: [0; 2],
+        })
+    }; EVENT_TRACE_ENTRIES],
+};
+
+/// Records an event. Use [`trace_event!`](crate::trace_event) instead, which
+/// compiles to nothing when `EVENT_TRACE` is disabled.
+#[inline(always)]
+pub fn record(id: u32, a: usize, b: usize) {
+    let i = RING.head.fetch_add(1, Ordering::Relaxed) & (EVENT_TRACE_ENTRIES - 1);
+    unsafe {
+        RING.slots[i].get().write(Event {
+            cycles: time::get_sys_cycles(),
+            id,
+            args: [a, b],
+        })
+    };
+}
+
+/// Calls `f` on the last `n` events, oldest first.
+pub fn for_each_recent<F: FnMut(&Event)>(n: usize, mut f: F) {
+    let head = RING.head.load(Ordering::Relaxed);
+    let n = n.min(head).min(EVENT_TRACE_ENTRIES);
+    for seq in head - n..head {
+        let event = unsafe { RING.slots[seq & (EVENT_TRACE_ENTRIES - 1)].get().read() };
+        f(&event);
+    }
+}
+
+/// Prints the last `n` events to the console.
+pub fn dump(n: usize) {
+    semihosting::println!("==== EVENT TRACE ====");
+    for_each_recent(n, |e| {
+        semihosting::println!(
+            "[{:>16}] {:#06x} {:#x} {:#x}",
+            e.cycles,
+            e.id,
+            e.args[0],
+            e.args[1]
+        );
+    });
+}
+
+/// Prints the whole ring.
+pub fn dump_all() {
+    dump(EVENT_TRACE_ENTRIES);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use blueos_test_macro::test;
+
+    #[test]
+    fn test_trace_ring_wraps() {
+        let base = 0x7e00_0000;
+        for i in 0..EVENT_TRACE_ENTRIES + 3 {
+            record(base, i, !i);
+        }
+        let mut seen = Vec::new();
+        for_each_recent(EVENT_TRACE_ENTRIES, |e| seen.push(*e));
+        assert_eq!(seen.len(), EVENT_TRACE_ENTRIES);
+        for (i, e) in seen.iter().enumerate() {
+            assert_eq!(e.id, base);
+            assert_eq!(e.args, [i + 3, !(i + 3)]);
+        }
+        assert!(seen.windows(2).all(|w| w[0].cycles <= w[1].cycles));
+    }
+}
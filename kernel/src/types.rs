@@ -70,6 +70,15 @@ pub(crate) trait StaticListOwner<T, A: IntrusiveAdapter<T>> {
     fn get() -> &'static Arc<SpinLock<AtomicIlistHead<T, A>>>;
 }
 
+/// A list with a single, statically known owner lock.
+///
+/// Every mutation takes the owner's `irqsave_lock` for its whole duration.
+/// That lock is not fair: when several CPUs contend, whichever wins the
+/// compare-exchange goes first, so the order of concurrent inserts is
+/// unspecified. Callers needing FIFO admission among CPUs should serialize
+/// through a [`blueos_infra::ticketlock::TicketLock`] instead. On single core
+/// builds the irqsave lock is never contended and inserts happen in program
+/// order.
 #[derive(Debug, Default)]
 pub(crate) struct UniqueListHead<T, A: IntrusiveAdapter<T>, O: StaticListOwner<T, A>>(
     AtomicIlistHead<T, A>,
@@ -103,6 +112,27 @@ impl<T: 'static, A: IntrusiveAdapter<T> + 'static, O: StaticListOwner<T, A>>
         ArcList::<T, A>::insert_after(&mut *self.0, me)
     }
 
+    /// Moves `me` from this list to the front of `to`, where `insert` puts
+    /// nodes, while both locks are held so that `me` is never observed on
+    /// neither list. Returns false if `me` is not on a list.
+    pub fn move_to_front<O2: StaticListOwner<T, A>>(
+        &mut self,
+        me: &mut Arc<T>,
+        to: &mut UniqueListHeadAccessGuard<T, A, O2>,
+    ) -> bool {
+        debug_assert!(
+            unsafe { ArcList::<T, A>::list_head_of_mut_unchecked(me) }.is_detached()
+                || ArcList::<T, A>::is_linked_in(me, &self.0, None),
+            "move_to_front: the node is on another list"
+        );
+        if !ArcList::<T, A>::detach(me) {
+            return false;
+        }
+        let ok = ArcList::<T, A>::insert_after(&mut to.0, me.clone());
+        debug_assert!(ok);
+        true
+    }
+
     #[inline]
     pub fn get_list_mut(&mut self) -> &mut AtomicIlistHead<T, A> {
         &mut self.0
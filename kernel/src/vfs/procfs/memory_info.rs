@@ -34,6 +34,20 @@ impl ProcFileOps for MemoryInfo {
             meminfo.max_used / 1024
         )
         .unwrap();
+        writeln!(
+            result,
+            "{:<14}{:>8} kB",
+            "MemMaxFree:",
+            allocator::get_max_free_block_size() / 1024
+        )
+        .unwrap();
+        writeln!(
+            result,
+            "{:<14}{:>8} %",
+            "MemFragment:",
+            allocator::fragmentation()
+        )
+        .unwrap();
         Ok(result.as_bytes().to_vec())
     }
 